@@ -0,0 +1,122 @@
+// Measures how long the server takes to apply a keystroke to a large document, comparing the
+// incremental reparse against parsing the whole file again. Besides the reparse alone, it times
+// everything `textDocument/didChange` does with it: updating the workspace index and computing
+// the diagnostics to publish. Run with `cargo bench`.
+
+use std::time::{Duration, Instant};
+
+use hurlsp::document::{Document, PositionEncoding};
+use hurlsp::symbols::ExternalVariables;
+use hurlsp::workspace::WorkspaceIndex;
+
+const ITERATIONS: u32 = 200;
+
+fn main() {
+    let text = large_file(5_000);
+    let lines = text.lines().count();
+    let middle: u32 = (lines / 2).try_into().unwrap();
+    let uri = lsp_types::Url::parse("file:///bench.hurl").unwrap();
+    let externals = ExternalVariables::default();
+    let mut index = WorkspaceIndex::default();
+
+    let replace = lsp_types::TextDocumentContentChangeEvent {
+        range: None,
+        range_length: None,
+        text: text.clone(),
+    };
+    // Type a character in the middle of the file then delete it again.
+    let position = lsp_types::Position {
+        line: middle,
+        character: 0,
+    };
+    let insert = lsp_types::TextDocumentContentChangeEvent {
+        range: Some(lsp_types::Range {
+            start: position,
+            end: position,
+        }),
+        range_length: None,
+        text: "x".to_string(),
+    };
+    let delete = lsp_types::TextDocumentContentChangeEvent {
+        range: Some(lsp_types::Range {
+            start: position,
+            end: lsp_types::Position {
+                line: middle,
+                character: 1,
+            },
+        }),
+        range_length: None,
+        text: "".to_string(),
+    };
+
+    let mut document = Document::new(&text, 0, PositionEncoding::Utf16);
+    let mut version = 0;
+    let mut reparse = |document: &mut Document,
+                       change: &lsp_types::TextDocumentContentChangeEvent| {
+        version += 1;
+        document
+            .apply_changes(version, std::slice::from_ref(change))
+            .unwrap();
+    };
+
+    let full = time(|| reparse(&mut document, &replace));
+    let incremental = time(|| {
+        reparse(&mut document, &insert);
+        reparse(&mut document, &delete);
+    }) / 2;
+
+    // Same as what the server does on `textDocument/didChange`.
+    let mut did_change =
+        |document: &mut Document, change: &lsp_types::TextDocumentContentChangeEvent| {
+            reparse(document, change);
+            index.update(uri.clone(), document);
+            document.diagnostics(&externals)
+        };
+    let full_did_change = time(|| {
+        did_change(&mut document, &replace);
+    });
+    let incremental_did_change = time(|| {
+        did_change(&mut document, &insert);
+        did_change(&mut document, &delete);
+    }) / 2;
+
+    println!("{} lines, {} iterations", lines, ITERATIONS);
+    println!("full reparse:          {:?} per change", full);
+    println!("incremental reparse:   {:?} per change", incremental);
+    println!("full didChange:        {:?} per change", full_did_change);
+    println!(
+        "incremental didChange: {:?} per change",
+        incremental_did_change
+    );
+}
+
+fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+/// A file of `lines` lines made of entries exercising headers, sections and bodies.
+fn large_file(lines: usize) -> String {
+    let entry = r#"# Create an item
+POST https://example.org/api/items
+Content-Type: application/json
+Authorization: Bearer {{token}}
+{
+    "name": "item",
+    "tags": ["a", "b"]
+}
+HTTP 201
+[Captures]
+item_id: jsonpath "$.id"
+[Asserts]
+header "Location" contains "/items/"
+jsonpath "$.name" == "item"
+jsonpath "$.tags" count == 2
+
+"#;
+    let entry_lines = entry.lines().count();
+    entry.repeat(lines / entry_lines + 1)
+}
@@ -0,0 +1,457 @@
+// Typed tree of a Hurl file. Every node carries the span of the source it was parsed from so
+// the language features can map between cursor positions and nodes.
+
+/// Zero-based line and character (counted in chars) of a position in the source.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Pos {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Pos {
+    pub fn new(line: usize, column: usize) -> Self {
+        Pos { line, column }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: Pos,
+    pub end: Pos,
+}
+
+impl Span {
+    pub fn new(start: Pos, end: Pos) -> Self {
+        Span { start, end }
+    }
+
+    /// Span covering `start..end` characters of a single line.
+    pub fn on_line(line: usize, start: usize, end: usize) -> Self {
+        Span {
+            start: Pos::new(line, start),
+            end: Pos::new(line, end),
+        }
+    }
+
+    /// Whether `pos` is inside the span. The end is inclusive so a cursor placed right after a
+    /// token still counts as being on it.
+    pub fn contains(&self, pos: Pos) -> bool {
+        self.start <= pos && pos <= self.end
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct HurlFile {
+    pub entries: Vec<Entry>,
+}
+
+impl HurlFile {
+    /// Entry owning `line`. An entry owns every line from its request line up to the request
+    /// line of the next entry, including trailing blank lines and comments.
+    pub fn entry_at(&self, line: usize) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .take_while(|entry| entry.span.start.line <= line)
+            .last()
+    }
+
+    /// Bodies of the requests and responses, in the order of the document.
+    pub fn bodies(&self) -> impl Iterator<Item = &Body> {
+        self.entries.iter().flat_map(|entry| {
+            let response_body = entry
+                .response
+                .as_ref()
+                .and_then(|response| response.body.as_ref());
+            entry.request.body.iter().chain(response_body)
+        })
+    }
+
+    /// ``` string values of the predicates, from their opening to their closing fence.
+    pub fn multiline_values(&self) -> impl Iterator<Item = &Spanned<Value>> {
+        self.entries
+            .iter()
+            .flat_map(|entry| entry.sections())
+            .filter_map(|section| match &section.content {
+                SectionContent::Asserts(asserts) => Some(asserts),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|assert| assert.predicate.as_ref()?.value.as_ref())
+            .filter(|value| matches!(value.value, Value::MultilineString(_)))
+    }
+
+    /// For each of the first `line_count` lines, whether it is content taken as is where `#`
+    /// doesn't start a comment: a line of a body or a line after the opening fence of a ```
+    /// predicate value.
+    pub fn verbatim_lines(&self, line_count: usize) -> Vec<bool> {
+        let mut verbatim = vec![false; line_count];
+        let bodies = self
+            .bodies()
+            .map(|body| (body.span.start.line, body.span.end.line));
+        let values = self
+            .multiline_values()
+            .map(|value| (value.span.start.line + 1, value.span.end.line));
+        for (start, end) in bodies.chain(values) {
+            let end = (end + 1).min(line_count);
+            if start < end {
+                verbatim[start..end].fill(true);
+            }
+        }
+        verbatim
+    }
+
+    /// Section owning `line`, from its `[Name]` header up to whatever comes next in the entry.
+    pub fn section_at(&self, line: usize) -> Option<(&Entry, &Section)> {
+        let entry = self.entry_at(line)?;
+        entry.section_at(line).map(|section| (entry, section))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub span: Span,
+    pub request: Request,
+    pub response: Option<Response>,
+}
+
+impl Entry {
+    /// Sections of the request followed by the sections of the response.
+    pub fn sections(&self) -> impl Iterator<Item = &Section> {
+        self.request.sections.iter().chain(
+            self.response
+                .iter()
+                .flat_map(|response| response.sections.iter()),
+        )
+    }
+
+    pub fn section_at(&self, line: usize) -> Option<&Section> {
+        let section = self
+            .sections()
+            .take_while(|section| section.span.start.line <= line)
+            .last()?;
+        let header_line = section.span.start.line;
+        let next_line = self
+            .boundaries()
+            .filter(|boundary| *boundary > header_line)
+            .min();
+        match next_line {
+            Some(next_line) if line >= next_line => None,
+            _ => Some(section),
+        }
+    }
+
+    /// Lines where a section, a body or the response starts.
+    fn boundaries(&self) -> impl Iterator<Item = usize> + '_ {
+        let request = self
+            .request
+            .sections
+            .iter()
+            .map(|section| section.span.start.line)
+            .chain(self.request.body.iter().map(|body| body.span.start.line));
+        let response = self.response.iter().flat_map(|response| {
+            std::iter::once(response.span.start.line)
+                .chain(
+                    response
+                        .sections
+                        .iter()
+                        .map(|section| section.span.start.line),
+                )
+                .chain(response.body.iter().map(|body| body.span.start.line))
+        });
+        request.chain(response)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub span: Span,
+    pub method: Spanned<String>,
+    pub url: Spanned<String>,
+    pub headers: Vec<KeyValue>,
+    pub sections: Vec<Section>,
+    pub body: Option<Body>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub span: Span,
+    pub version: Spanned<String>,
+    pub status: Spanned<String>,
+    pub headers: Vec<KeyValue>,
+    pub sections: Vec<Section>,
+    pub body: Option<Body>,
+}
+
+/// A `key: value` line, used for headers and for the request sections.
+#[derive(Debug, Clone)]
+pub struct KeyValue {
+    pub span: Span,
+    pub key: Spanned<String>,
+    pub value: Spanned<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionKind {
+    QueryStringParams,
+    FormParams,
+    MultipartFormData,
+    Cookies,
+    Options,
+    BasicAuth,
+    Captures,
+    Asserts,
+}
+
+impl SectionKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "QueryStringParams" | "Query" => Some(SectionKind::QueryStringParams),
+            "FormParams" | "Form" => Some(SectionKind::FormParams),
+            "MultipartFormData" | "Multipart" => Some(SectionKind::MultipartFormData),
+            "Cookies" => Some(SectionKind::Cookies),
+            "Options" => Some(SectionKind::Options),
+            "BasicAuth" => Some(SectionKind::BasicAuth),
+            "Captures" => Some(SectionKind::Captures),
+            "Asserts" => Some(SectionKind::Asserts),
+            _ => None,
+        }
+    }
+
+    /// Whether the section goes after the response line rather than in the request.
+    pub fn is_response_section(self) -> bool {
+        matches!(self, SectionKind::Captures | SectionKind::Asserts)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub span: Span,
+    /// The section name without the surrounding brackets, spanning the whole `[Name]` header.
+    pub name: Spanned<String>,
+    pub kind: SectionKind,
+    pub content: SectionContent,
+}
+
+#[derive(Debug, Clone)]
+pub enum SectionContent {
+    KeyValues(Vec<KeyValue>),
+    Captures(Vec<Capture>),
+    Asserts(Vec<Assert>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Capture {
+    pub span: Span,
+    pub name: Spanned<String>,
+    pub query: Option<Query>,
+    pub filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Assert {
+    pub span: Span,
+    pub query: Query,
+    pub filters: Vec<Filter>,
+    pub predicate: Option<Predicate>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Query {
+    pub span: Span,
+    pub name: Spanned<String>,
+    pub argument: Option<Spanned<Value>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Filter {
+    pub span: Span,
+    pub name: Spanned<String>,
+    pub arguments: Vec<Spanned<Value>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Predicate {
+    pub span: Span,
+    pub not: Option<Span>,
+    pub name: Spanned<String>,
+    pub value: Option<Spanned<Value>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    MultilineString(String),
+    Regex(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    Null,
+    Template(String),
+    /// `base64,...;`, `hex,...;` or `file,...;` literal, kept verbatim.
+    Bytes(String),
+    /// Any other unquoted word.
+    Word(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyKind {
+    Json,
+    Xml,
+    MultilineString,
+    OnelineString,
+    QuotedString,
+    Base64,
+    Hex,
+    File,
+}
+
+#[derive(Debug, Clone)]
+pub struct Body {
+    pub span: Span,
+    pub kind: BodyKind,
+}
+
+/// Moves a node by a number of lines, used to reuse the tree of entries following an edit.
+pub trait Shift {
+    fn shift(&mut self, lines: isize);
+}
+
+impl Shift for Pos {
+    fn shift(&mut self, lines: isize) {
+        self.line = self.line.saturating_add_signed(lines);
+    }
+}
+
+impl Shift for Span {
+    fn shift(&mut self, lines: isize) {
+        self.start.shift(lines);
+        self.end.shift(lines);
+    }
+}
+
+impl<T> Shift for Spanned<T> {
+    fn shift(&mut self, lines: isize) {
+        self.span.shift(lines);
+    }
+}
+
+impl<T: Shift> Shift for Option<T> {
+    fn shift(&mut self, lines: isize) {
+        if let Some(node) = self {
+            node.shift(lines);
+        }
+    }
+}
+
+impl<T: Shift> Shift for Vec<T> {
+    fn shift(&mut self, lines: isize) {
+        for node in self {
+            node.shift(lines);
+        }
+    }
+}
+
+impl Shift for Entry {
+    fn shift(&mut self, lines: isize) {
+        self.span.shift(lines);
+        self.request.shift(lines);
+        self.response.shift(lines);
+    }
+}
+
+impl Shift for Request {
+    fn shift(&mut self, lines: isize) {
+        self.span.shift(lines);
+        self.method.shift(lines);
+        self.url.shift(lines);
+        self.headers.shift(lines);
+        self.sections.shift(lines);
+        self.body.shift(lines);
+    }
+}
+
+impl Shift for Response {
+    fn shift(&mut self, lines: isize) {
+        self.span.shift(lines);
+        self.version.shift(lines);
+        self.status.shift(lines);
+        self.headers.shift(lines);
+        self.sections.shift(lines);
+        self.body.shift(lines);
+    }
+}
+
+impl Shift for KeyValue {
+    fn shift(&mut self, lines: isize) {
+        self.span.shift(lines);
+        self.key.shift(lines);
+        self.value.shift(lines);
+    }
+}
+
+impl Shift for Section {
+    fn shift(&mut self, lines: isize) {
+        self.span.shift(lines);
+        self.name.shift(lines);
+        match &mut self.content {
+            SectionContent::KeyValues(key_values) => key_values.shift(lines),
+            SectionContent::Captures(captures) => captures.shift(lines),
+            SectionContent::Asserts(asserts) => asserts.shift(lines),
+        }
+    }
+}
+
+impl Shift for Capture {
+    fn shift(&mut self, lines: isize) {
+        self.span.shift(lines);
+        self.name.shift(lines);
+        self.query.shift(lines);
+        self.filters.shift(lines);
+    }
+}
+
+impl Shift for Assert {
+    fn shift(&mut self, lines: isize) {
+        self.span.shift(lines);
+        self.query.shift(lines);
+        self.filters.shift(lines);
+        self.predicate.shift(lines);
+    }
+}
+
+impl Shift for Query {
+    fn shift(&mut self, lines: isize) {
+        self.span.shift(lines);
+        self.name.shift(lines);
+        self.argument.shift(lines);
+    }
+}
+
+impl Shift for Filter {
+    fn shift(&mut self, lines: isize) {
+        self.span.shift(lines);
+        self.name.shift(lines);
+        self.arguments.shift(lines);
+    }
+}
+
+impl Shift for Predicate {
+    fn shift(&mut self, lines: isize) {
+        self.span.shift(lines);
+        self.not.shift(lines);
+        self.name.shift(lines);
+        self.value.shift(lines);
+    }
+}
+
+impl Shift for Body {
+    fn shift(&mut self, lines: isize) {
+        self.span.shift(lines);
+    }
+}
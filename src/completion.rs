@@ -0,0 +1,754 @@
+use crate::ast::{Entry, Filter, Pos, Query, SectionContent, SectionKind, Span};
+use crate::document::Document;
+use crate::documentation::DOCUMENTATION_MAP;
+use crate::grammar::{
+    self, FILTERS, METHODS, OPTIONS, PREDICATES, QUERIES, SECTIONS, STATUS_CODES, VERSIONS,
+};
+use crate::headers;
+use crate::parser;
+use crate::symbols::{ExternalVariables, Origin};
+
+/// Syntactic position of the cursor, deciding which items are valid there.
+#[derive(Debug, Clone, PartialEq)]
+enum Context {
+    Method,
+    Url,
+    /// Name of a header of the request or, with `response`, of the response.
+    HeaderName {
+        response: bool,
+    },
+    /// Value of the header with the given name.
+    HeaderValue(String),
+    /// HTTP version starting the response line.
+    Version,
+    Status,
+    SectionHeader,
+    Query,
+    Filter,
+    /// Predicate of an assert, `negated` once `not` has been typed.
+    Predicate {
+        negated: bool,
+    },
+    OptionKey,
+    /// Variable name inside `{{ }}`.
+    Template,
+}
+
+pub fn get_completions(
+    document: &Document,
+    position: lsp_types::Position,
+    externals: &ExternalVariables,
+) -> Vec<lsp_types::CompletionItem> {
+    let positions = document.positions();
+    let pos = positions.pos(position);
+    let Some((contexts, span)) = get_context(document, pos) else {
+        return vec![];
+    };
+    contexts
+        .iter()
+        .flat_map(|context| get_context_completions(document, pos, context, externals))
+        .map(|mut item| {
+            let new_text = item
+                .insert_text
+                .take()
+                .unwrap_or_else(|| item.label.clone());
+            item.text_edit = Some(lsp_types::CompletionTextEdit::Edit(lsp_types::TextEdit {
+                range: positions.range(span),
+                new_text,
+            }));
+            item
+        })
+        .collect()
+}
+
+/// Contexts valid at `pos` along with the span of the text a completion replaces.
+fn get_context(document: &Document, pos: Pos) -> Option<(Vec<Context>, Span)> {
+    let line = document.line(pos.line);
+    let column = pos.column.min(line.len());
+    let pos = Pos::new(pos.line, column);
+    // Span of the word around the cursor made of the chars accepted by `is_word`.
+    let word = |is_word: fn(char) -> bool| {
+        let start = (0..column)
+            .rev()
+            .find(|i| !is_word(line[*i]))
+            .map_or(0, |i| i + 1);
+        let end = (column..line.len())
+            .find(|i| !is_word(line[*i]))
+            .unwrap_or(line.len());
+        Span::on_line(pos.line, start, end)
+    };
+    let non_blank = |c: char| !c.is_whitespace();
+    let key = |c: char| !c.is_whitespace() && c != ':';
+
+    // Templates can be used almost anywhere so they are looked for before anything else.
+    let before = &line[..column];
+    if let Some(open) = (1..column).rev().find(|i| before[i - 1..=*i] == ['{', '{']) {
+        if !before[open..].contains(&'}') {
+            let span = word(|c| c.is_alphanumeric() || c == '_' || c == '-');
+            return Some((vec![Context::Template], span));
+        }
+    }
+    if line[parser::content_end(line).min(column)..column].contains(&'#') {
+        return None;
+    }
+    let indent = parser::indent(line).min(column);
+    let first_word = !before[indent..].iter().any(|c| c.is_whitespace());
+    if first_word && line.get(indent) == Some(&'[') {
+        let mut span = word(non_blank);
+        span.start.column = indent;
+        return Some((vec![Context::SectionHeader], span));
+    }
+
+    let Some(entry) = document.tree.entry_at(pos.line) else {
+        return first_word.then(|| (vec![Context::Method], word(non_blank)));
+    };
+    let request = &entry.request;
+    if pos.line == request.span.start.line {
+        if !first_word {
+            return Some((vec![Context::Url], word(non_blank)));
+        }
+        // A lone word is parsed as a new entry but may as well be a header of the previous one
+        // being typed.
+        let previous = document
+            .tree
+            .entries
+            .iter()
+            .take_while(|entry| entry.span.start.line < pos.line)
+            .last();
+        let mut contexts = vec![Context::Method];
+        if let Some(previous) = previous.filter(|_| request.url.value.is_empty()) {
+            if in_headers(previous, pos.line) {
+                contexts.push(Context::HeaderName {
+                    response: after_response_line(previous, pos.line),
+                });
+            }
+            if previous.response.is_none() {
+                contexts.push(Context::Version);
+            }
+        }
+        return Some((contexts, word(key)));
+    }
+    if let Some(response) = &entry.response {
+        if pos.line == response.span.start.line {
+            let context = if first_word {
+                Context::Version
+            } else if response.status.value.is_empty() || column <= response.status.span.end.column
+            {
+                // An empty status spans nothing before the trailing whitespace of `HTTP `.
+                Context::Status
+            } else {
+                return None;
+            };
+            return Some((vec![context], word(non_blank)));
+        }
+    }
+    // Until the entry has a response, its status line can start any line a new entry could.
+    let next_line_contexts = |mut contexts: Vec<Context>| {
+        if entry.response.is_none() {
+            contexts.push(Context::Version);
+        }
+        contexts
+    };
+    // Bodies and ``` strings are free text, only their templates complete.
+    if document.tree.verbatim_lines(pos.line + 1)[pos.line] {
+        return None;
+    }
+
+    if let Some(section) = entry.section_at(pos.line) {
+        if pos.line == section.span.start.line {
+            return None;
+        }
+        return match &section.content {
+            SectionContent::Asserts(asserts) => {
+                let assert = asserts
+                    .iter()
+                    .find(|assert| assert.span.start.line == pos.line);
+                match assert {
+                    Some(assert) if column > assert.query.name.span.end.column => {
+                        // Past the query and its filters, the words typed so far decide whether
+                        // a filter, a predicate or the predicate after `not` is expected.
+                        let words = typed_words(line, pos, &assert.query, &assert.filters)?;
+                        let contexts = match words.as_slice() {
+                            [] => vec![Context::Filter, Context::Predicate { negated: false }],
+                            [word] if word == "not" => vec![Context::Predicate { negated: true }],
+                            _ => return None,
+                        };
+                        Some((contexts, word(non_blank)))
+                    }
+                    _ if first_word => Some((vec![Context::Query], word(non_blank))),
+                    _ => None,
+                }
+            }
+            SectionContent::Captures(captures) => {
+                let colon = before.iter().position(|c| *c == ':')?;
+                let capture = captures
+                    .iter()
+                    .find(|capture| capture.span.start.line == pos.line);
+                match capture.and_then(|capture| Some((capture, capture.query.as_ref()?))) {
+                    Some((capture, query)) if column > query.name.span.end.column => {
+                        match typed_words(line, pos, query, &capture.filters)?.as_slice() {
+                            [] => Some((vec![Context::Filter], word(non_blank))),
+                            _ => None,
+                        }
+                    }
+                    // The query is the first word after the colon.
+                    _ if !before[colon + 1..]
+                        .iter()
+                        .skip_while(|c| c.is_whitespace())
+                        .any(|c| c.is_whitespace()) =>
+                    {
+                        let mut span = word(non_blank);
+                        span.start.column = span.start.column.max(colon + 1);
+                        Some((vec![Context::Query], span))
+                    }
+                    _ => None,
+                }
+            }
+            SectionContent::KeyValues(_) => {
+                if !first_word || before.contains(&':') {
+                    return None;
+                }
+                let mut contexts = vec![];
+                if section.kind == SectionKind::Options {
+                    contexts.push(Context::OptionKey);
+                }
+                Some((next_line_contexts(contexts), word(key)))
+            }
+        };
+    }
+
+    // Outside of the sections and bodies, a line holds a header when it is in the header block
+    // of the request or the response, and otherwise can only start the next entry.
+    if !in_headers(entry, pos.line) {
+        return first_word.then(|| (next_line_contexts(vec![Context::Method]), word(non_blank)));
+    }
+    if let Some(colon) = before.iter().position(|c| *c == ':') {
+        // Values such as `Accept` are lists, only the item at the cursor is replaced.
+        let name: String = line[indent..colon].iter().collect();
+        let content_end = parser::content_end(line).max(column);
+        let item_start = (colon + 1..column)
+            .rev()
+            .find(|i| line[*i] == ',')
+            .map_or(colon + 1, |i| i + 1);
+        let start = parser::skip_whitespace(line, item_start, column);
+        let end = (column..content_end)
+            .find(|i| line[*i] == ',')
+            .unwrap_or(content_end);
+        return Some((
+            vec![Context::HeaderValue(name.trim().to_string())],
+            Span::on_line(pos.line, start, end),
+        ));
+    }
+    let header_name = Context::HeaderName {
+        response: after_response_line(entry, pos.line),
+    };
+    first_word.then(|| {
+        (
+            next_line_contexts(vec![Context::Method, header_name]),
+            word(key),
+        )
+    })
+}
+
+fn after_response_line(entry: &Entry, line: usize) -> bool {
+    entry
+        .response
+        .as_ref()
+        .is_some_and(|response| response.span.start.line < line)
+}
+
+/// Whether `line` is in the block of headers following the request or the response line.
+fn in_headers(entry: &Entry, line: usize) -> bool {
+    let request = &entry.request;
+    let response_line = entry
+        .response
+        .as_ref()
+        .map(|response| response.span.start.line);
+    let request_end = request
+        .sections
+        .iter()
+        .map(|section| section.span.start.line)
+        .chain(request.body.iter().map(|body| body.span.start.line))
+        .chain(response_line)
+        .min();
+    if line > request.span.start.line && request_end.is_none_or(|end| line < end) {
+        return true;
+    }
+    let Some(response) = &entry.response else {
+        return false;
+    };
+    let response_end = response
+        .sections
+        .iter()
+        .map(|section| section.span.start.line)
+        .chain(response.body.iter().map(|body| body.span.start.line))
+        .min();
+    line > response.span.start.line && response_end.is_none_or(|end| line < end)
+}
+
+/// Words typed between the end of a query and its filters and the cursor, without the word the
+/// cursor is on. `None` if the cursor isn't past them or the last filter still expects arguments.
+fn typed_words(line: &[char], pos: Pos, query: &Query, filters: &[Filter]) -> Option<Vec<String>> {
+    if let Some(filter) = filters.last() {
+        let arity = grammar::filter_arguments(&filter.name.value).map_or(0, <[_]>::len);
+        if filter.arguments.len() < arity {
+            return None;
+        }
+    }
+    let after = filters
+        .last()
+        .map(|filter| filter.span.end)
+        .unwrap_or(query.span.end);
+    if pos.column <= after.column {
+        return None;
+    }
+    let typed: String = line[after.column..pos.column].iter().collect();
+    if !typed.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let mut words: Vec<String> = typed.split_whitespace().map(str::to_string).collect();
+    if !typed.ends_with(char::is_whitespace) {
+        words.pop();
+    }
+    Some(words)
+}
+
+fn get_context_completions(
+    document: &Document,
+    pos: Pos,
+    context: &Context,
+    externals: &ExternalVariables,
+) -> Vec<lsp_types::CompletionItem> {
+    match context {
+        Context::Method => get_method_completions(document, pos),
+        Context::Version => get_version_completions(),
+        Context::Status => get_status_completions(),
+        Context::SectionHeader => get_section_completions(document, pos),
+        Context::Query => get_query_completions(),
+        Context::Filter => get_filter_completions(),
+        Context::Predicate { negated } => {
+            let mut items = get_predicate_completions();
+            if !negated {
+                items.push(make_item("not", lsp_types::CompletionItemKind::KEYWORD));
+            }
+            items
+        }
+        Context::HeaderName { response } => get_header_name_completions(*response),
+        Context::HeaderValue(name) => get_header_value_completions(name),
+        Context::OptionKey => get_option_completions(),
+        Context::Template => get_variable_completions(document, pos, externals),
+        Context::Url => vec![],
+    }
+}
+
+/// Standard methods followed by the custom ones already used in other entries of the file.
+fn get_method_completions(document: &Document, pos: Pos) -> Vec<lsp_types::CompletionItem> {
+    let mut items: Vec<lsp_types::CompletionItem> = METHODS
+        .iter()
+        .map(|name| make_item(name, lsp_types::CompletionItemKind::KEYWORD))
+        .collect();
+    let mut custom: Vec<&str> = document
+        .tree
+        .entries
+        .iter()
+        .filter(|entry| !entry.request.url.value.is_empty())
+        .map(|entry| &entry.request.method)
+        .filter(|method| method.span.start.line != pos.line)
+        .map(|method| method.value.as_str())
+        .filter(|method| {
+            !method.is_empty()
+                && method.chars().all(|c| c.is_ascii_uppercase())
+                && !METHODS.contains(method)
+        })
+        .collect();
+    custom.sort_unstable();
+    custom.dedup();
+    items.extend(custom.into_iter().map(|name| lsp_types::CompletionItem {
+        detail: Some("Custom method".to_string()),
+        ..make_item(name, lsp_types::CompletionItemKind::KEYWORD)
+    }));
+    items
+}
+
+fn get_header_name_completions(response: bool) -> Vec<lsp_types::CompletionItem> {
+    headers::get_headers()
+        .iter()
+        .filter(|header| {
+            if response {
+                header.in_response()
+            } else {
+                header.in_request()
+            }
+        })
+        .map(|header| lsp_types::CompletionItem {
+            documentation: Some(markdown(&header.documentation())),
+            ..make_item(&header.name, lsp_types::CompletionItemKind::FIELD)
+        })
+        .collect()
+}
+
+fn get_header_value_completions(name: &str) -> Vec<lsp_types::CompletionItem> {
+    let Some(header) = headers::find_header(name) else {
+        return vec![];
+    };
+    header
+        .values
+        .iter()
+        .map(|value| lsp_types::CompletionItem {
+            insert_text: Some(value.clone()),
+            ..make_item(value.trim(), lsp_types::CompletionItemKind::VALUE)
+        })
+        .collect()
+}
+
+fn get_option_completions() -> Vec<lsp_types::CompletionItem> {
+    OPTIONS
+        .iter()
+        .map(|(name, value)| lsp_types::CompletionItem {
+            detail: Some(format!("Expects {}", value.description())),
+            documentation: DOCUMENTATION_MAP
+                .get(format!("options/{}", name).as_str())
+                .map(|value| markdown(value)),
+            ..make_item(name, lsp_types::CompletionItemKind::PROPERTY)
+        })
+        .collect()
+}
+
+fn get_version_completions() -> Vec<lsp_types::CompletionItem> {
+    VERSIONS
+        .iter()
+        .map(|(version, detail)| lsp_types::CompletionItem {
+            detail: Some(detail.to_string()),
+            ..make_item(version, lsp_types::CompletionItemKind::KEYWORD)
+        })
+        .collect()
+}
+
+fn get_status_completions() -> Vec<lsp_types::CompletionItem> {
+    STATUS_CODES
+        .iter()
+        .map(|(code, reason, description)| lsp_types::CompletionItem {
+            detail: Some(reason.to_string()),
+            documentation: Some(markdown(description)),
+            ..make_item(code, lsp_types::CompletionItemKind::VALUE)
+        })
+        .collect()
+}
+
+/// Sections allowed in the part of the entry the cursor is in, request or response, leaving out
+/// those already there.
+fn get_section_completions(document: &Document, pos: Pos) -> Vec<lsp_types::CompletionItem> {
+    let entry = document.tree.entry_at(pos.line);
+    let response = entry.and_then(|entry| entry.response.as_ref());
+    let in_response = response.is_some_and(|response| response.span.start.line < pos.line);
+    let existing: Vec<SectionKind> = match (entry, response) {
+        (Some(_), Some(response)) if in_response => response.sections.iter().collect(),
+        (Some(entry), _) => entry.request.sections.iter().collect(),
+        (None, _) => vec![],
+    }
+    .into_iter()
+    .filter(|section| section.span.start.line != pos.line)
+    .map(|section| section.kind)
+    .collect();
+
+    SECTIONS
+        .iter()
+        .filter_map(|name| {
+            let kind = SectionKind::from_name(name)?;
+            if kind.is_response_section() != in_response || existing.contains(&kind) {
+                return None;
+            }
+            let detail = if kind.is_response_section() {
+                "Response section"
+            } else {
+                "Request section"
+            };
+            Some(lsp_types::CompletionItem {
+                detail: Some(detail.to_string()),
+                ..make_item(
+                    &format!("[{}]", name),
+                    lsp_types::CompletionItemKind::MODULE,
+                )
+            })
+        })
+        .collect()
+}
+
+/// Variables set by the time the cursor line runs, the closest definition of each name first,
+/// followed by the external ones that the document doesn't define.
+fn get_variable_completions(
+    document: &Document,
+    pos: Pos,
+    externals: &ExternalVariables,
+) -> Vec<lsp_types::CompletionItem> {
+    let mut names: Vec<&str> = vec![];
+    let mut items = vec![];
+    for variable in document.symbols.visible_at(pos.line) {
+        if names.contains(&variable.name.as_str()) {
+            continue;
+        }
+        names.push(&variable.name);
+        let detail = match variable.origin {
+            Origin::Capture => format!("Captured at line {}", variable.span.start.line + 1),
+            Origin::Option => format!(
+                "Defined in [Options] at line {}",
+                variable.span.start.line + 1
+            ),
+        };
+        items.push(lsp_types::CompletionItem {
+            label: variable.name.clone(),
+            kind: Some(lsp_types::CompletionItemKind::VARIABLE),
+            detail: Some(detail),
+            ..Default::default()
+        });
+    }
+    for (name, value) in &externals.options {
+        if names.contains(&name.as_str()) {
+            continue;
+        }
+        names.push(name);
+        items.push(lsp_types::CompletionItem {
+            label: name.clone(),
+            kind: Some(lsp_types::CompletionItemKind::VARIABLE),
+            detail: Some("Defined in the initialization options".to_string()),
+            documentation: Some(markdown(&format!("`{}`", value))),
+            ..Default::default()
+        });
+    }
+    if let Some(file) = &externals.file {
+        let file_name = file
+            .path
+            .file_name()
+            .map_or(file.path.to_string_lossy(), |name| name.to_string_lossy());
+        for variable in &file.variables {
+            if names.contains(&variable.name.as_str()) {
+                continue;
+            }
+            names.push(&variable.name);
+            items.push(lsp_types::CompletionItem {
+                label: variable.name.clone(),
+                kind: Some(lsp_types::CompletionItemKind::VARIABLE),
+                detail: Some(format!("Defined in {}", file_name)),
+                documentation: Some(markdown(&format!("`{}`", variable.value))),
+                ..Default::default()
+            });
+        }
+    }
+    items
+}
+
+fn get_query_completions() -> Vec<lsp_types::CompletionItem> {
+    QUERIES
+        .iter()
+        .map(|(name, _)| make_item(name, lsp_types::CompletionItemKind::TEXT))
+        .collect()
+}
+
+fn get_filter_completions() -> Vec<lsp_types::CompletionItem> {
+    FILTERS
+        .iter()
+        .map(|(name, _)| lsp_types::CompletionItem {
+            documentation: DOCUMENTATION_MAP
+                .get(format!("filters/{}", name).as_str())
+                .map(|value| markdown(value)),
+            ..make_item(name, lsp_types::CompletionItemKind::FUNCTION)
+        })
+        .collect()
+}
+
+fn get_predicate_completions() -> Vec<lsp_types::CompletionItem> {
+    PREDICATES
+        .iter()
+        .map(|(name, _)| make_item(name, lsp_types::CompletionItemKind::OPERATOR))
+        .collect()
+}
+
+fn make_item(label: &str, kind: lsp_types::CompletionItemKind) -> lsp_types::CompletionItem {
+    lsp_types::CompletionItem {
+        label: label.to_string(),
+        kind: Some(kind),
+        documentation: DOCUMENTATION_MAP.get(label).map(|value| markdown(value)),
+        ..Default::default()
+    }
+}
+
+fn markdown(value: &str) -> lsp_types::Documentation {
+    lsp_types::Documentation::MarkupContent(lsp_types::MarkupContent {
+        kind: lsp_types::MarkupKind::Markdown,
+        value: value.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::PositionEncoding;
+
+    /// Contexts at the `|` of `text` and the text their completions replace.
+    fn context(text: &str) -> Option<(Vec<Context>, String)> {
+        let (before, after) = text.split_once('|').unwrap();
+        let line = before.matches('\n').count();
+        let column = before.rsplit('\n').next().unwrap().chars().count();
+        let document = Document::new(&format!("{}{}", before, after), 0, PositionEncoding::Utf16);
+        let (contexts, span) = get_context(&document, Pos::new(line, column))?;
+        let replaced = document.line(line)[span.start.column..span.end.column]
+            .iter()
+            .collect();
+        Some((contexts, replaced))
+    }
+
+    /// Expected result of `context`.
+    fn found(contexts: Vec<Context>, replaced: &str) -> Option<(Vec<Context>, String)> {
+        Some((contexts, replaced.to_string()))
+    }
+
+    #[test]
+    fn request_line() {
+        assert_eq!(context("|"), found(vec![Context::Method], ""));
+        assert_eq!(context("G|"), found(vec![Context::Method], "G"));
+        assert_eq!(context("GET |"), found(vec![Context::Url], ""));
+        assert_eq!(
+            context("GET https://a|"),
+            found(vec![Context::Url], "https://a")
+        );
+    }
+
+    #[test]
+    fn headers() {
+        let request = vec![
+            Context::Method,
+            Context::HeaderName { response: false },
+            Context::Version,
+        ];
+        assert_eq!(context("GET https://a.org\nAcc|"), found(request, "Acc"));
+        assert_eq!(
+            context("GET https://a.org\nAccept: app|"),
+            found(vec![Context::HeaderValue("Accept".to_string())], "app")
+        );
+        assert_eq!(
+            context("GET https://a.org\nContent-Type: |"),
+            found(vec![Context::HeaderValue("Content-Type".to_string())], "")
+        );
+        assert_eq!(
+            context("GET https://a.org\nHTTP 200\nCont|"),
+            found(
+                vec![Context::Method, Context::HeaderName { response: true }],
+                "Cont"
+            )
+        );
+    }
+
+    #[test]
+    fn response_line() {
+        assert_eq!(
+            context("GET https://a.org\nHT|"),
+            found(
+                vec![
+                    Context::Method,
+                    Context::HeaderName { response: false },
+                    Context::Version
+                ],
+                "HT"
+            )
+        );
+        assert_eq!(
+            context("GET https://a.org\nHTTP |"),
+            found(vec![Context::Status], "")
+        );
+    }
+
+    #[test]
+    fn section_headers() {
+        assert_eq!(
+            context("GET https://a.org\n[Q|"),
+            found(vec![Context::SectionHeader], "[Q")
+        );
+        assert_eq!(
+            context("GET https://a.org\nHTTP 200\n[|"),
+            found(vec![Context::SectionHeader], "[")
+        );
+    }
+
+    #[test]
+    fn asserts_and_captures() {
+        let asserts = "GET https://a.org\nHTTP 200\n[Asserts]\n";
+        let after_query = vec![Context::Filter, Context::Predicate { negated: false }];
+        assert_eq!(
+            context(&format!("{}hea|", asserts)),
+            found(vec![Context::Query], "hea")
+        );
+        assert_eq!(
+            context(&format!("{}body |", asserts)),
+            found(after_query.clone(), "")
+        );
+        assert_eq!(
+            context(&format!("{}body con|", asserts)),
+            found(after_query.clone(), "con")
+        );
+        assert_eq!(
+            context(&format!("{}jsonpath \"$.a\" count |", asserts)),
+            found(after_query, "")
+        );
+        assert_eq!(
+            context(&format!("{}body not con|", asserts)),
+            found(vec![Context::Predicate { negated: true }], "con")
+        );
+        assert_eq!(
+            context(&format!("{}jsonpath \"$.a\" count == |", asserts)),
+            None
+        );
+        assert_eq!(
+            context("GET https://a.org\nHTTP 200\n[Captures]\nid: hea|"),
+            found(vec![Context::Query], "hea")
+        );
+    }
+
+    #[test]
+    fn option_keys() {
+        assert_eq!(
+            context("GET https://a.org\n[Options]\n|"),
+            found(vec![Context::OptionKey, Context::Version], "")
+        );
+        assert_eq!(
+            context("GET https://a.org\n[Options]\nins|"),
+            found(vec![Context::OptionKey, Context::Version], "ins")
+        );
+    }
+
+    #[test]
+    fn templates() {
+        assert_eq!(
+            context("GET https://a.org/{{|"),
+            found(vec![Context::Template], "")
+        );
+        assert_eq!(
+            context("GET https://a.org/{{to|}}"),
+            found(vec![Context::Template], "to")
+        );
+        assert_eq!(
+            context("GET https://a.org\nX-Token: {{to|}}"),
+            found(vec![Context::Template], "to")
+        );
+    }
+
+    #[test]
+    fn no_completion_in_multiline_strings() {
+        let asserts = "GET https://a.org\nHTTP 200\n[Asserts]\n";
+        assert_eq!(
+            context(&format!("{}body == ```\nfo|o\n```\n", asserts)),
+            None
+        );
+        assert_eq!(
+            context(&format!("{}body == ```\nfoo\n|```\n", asserts)),
+            None
+        );
+        assert_eq!(
+            context(&format!("{}body == ```\n{{{{na|}}}}\n```\n", asserts)),
+            Some((vec![Context::Template], "na".to_string()))
+        );
+        assert_eq!(
+            context(&format!("{}body == ```\nfoo\n```\nst|", asserts)),
+            Some((vec![Context::Query], "st".to_string()))
+        );
+    }
+}
@@ -0,0 +1,42 @@
+use crate::document::Document;
+use crate::symbols::ExternalVariables;
+
+/// Location defining the variable of the template under the cursor: the closest capture or
+/// `variable` option set by the time the template is rendered, else the line of the variables
+/// file supplying it. A variable only defined later in the document still leads to its
+/// definition so a misplaced capture can be found.
+pub fn get_definition(
+    document: &Document,
+    uri: &lsp_types::Url,
+    position: lsp_types::Position,
+    externals: &ExternalVariables,
+) -> Option<lsp_types::Location> {
+    let positions = document.positions();
+    let pos = positions.pos(position);
+    let symbols = &document.symbols;
+    let reference = symbols
+        .references
+        .iter()
+        .find(|reference| reference.span.contains(pos))?;
+    let name = reference.value.as_str();
+    let visible = symbols
+        .visible_at(reference.span.start.line)
+        .find(|variable| variable.name == name);
+    if let Some(variable) = visible {
+        return Some(lsp_types::Location::new(
+            uri.clone(),
+            positions.range(variable.span),
+        ));
+    }
+    if let Some(file) = &externals.file {
+        if let Some(variable) = file.find(name) {
+            let file_uri = lsp_types::Url::from_file_path(&file.path).ok()?;
+            let range = file.positions(document.encoding).range(variable.span);
+            return Some(lsp_types::Location::new(file_uri, range));
+        }
+    }
+    symbols
+        .find(name)
+        .next()
+        .map(|variable| lsp_types::Location::new(uri.clone(), positions.range(variable.span)))
+}
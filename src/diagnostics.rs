@@ -0,0 +1,452 @@
+use crate::ast::{
+    Entry, Filter, HurlFile, KeyValue, Predicate, Query, Response, SectionContent, SectionKind,
+    Span, Spanned, Value,
+};
+use crate::document::Positions;
+use crate::grammar::{
+    self, PredicateValue, QueryArgument, CERTIFICATE_FIELDS, OPTIONS, PREDICATES, QUERIES, VERSIONS,
+};
+use crate::parser::ParseError;
+use crate::symbols::{ExternalVariables, Origin, SymbolTable};
+
+pub fn get_diagnostics(tree: &HurlFile, positions: Positions) -> Vec<lsp_types::Diagnostic> {
+    let mut diagnostics = vec![];
+    for entry in &tree.entries {
+        check_method(&entry.request.method, &mut diagnostics, positions);
+        check_sections(entry, &mut diagnostics, positions);
+        if let Some(response) = &entry.response {
+            check_response(response, &mut diagnostics, positions);
+        }
+    }
+    let sections = tree.entries.iter().flat_map(|entry| entry.sections());
+    for section in sections {
+        match &section.content {
+            SectionContent::Asserts(asserts) => {
+                for assert in asserts {
+                    check_query(&assert.query, &mut diagnostics, positions);
+                    for filter in &assert.filters {
+                        check_filter(filter, &mut diagnostics, positions);
+                    }
+                    if let Some(predicate) = &assert.predicate {
+                        check_predicate(predicate, &mut diagnostics, positions);
+                    }
+                }
+            }
+            SectionContent::Captures(captures) => {
+                for capture in captures {
+                    check_capture_name(&capture.name, &mut diagnostics, positions);
+                    if let Some(query) = &capture.query {
+                        check_query(query, &mut diagnostics, positions);
+                    }
+                    for filter in &capture.filters {
+                        check_filter(filter, &mut diagnostics, positions);
+                    }
+                }
+            }
+            SectionContent::KeyValues(options) if section.kind == SectionKind::Options => {
+                for option in options {
+                    check_option(option, &mut diagnostics, positions);
+                }
+            }
+            SectionContent::KeyValues(_) => {}
+        }
+    }
+    diagnostics
+}
+
+fn check_capture_name(
+    name: &Spanned<String>,
+    diagnostics: &mut Vec<lsp_types::Diagnostic>,
+    positions: Positions,
+) {
+    if !grammar::is_variable_name(&name.value) {
+        diagnostics.push(make_diagnostic(
+            positions.range(name.span),
+            grammar::invalid_variable_name(&name.value),
+        ));
+    }
+}
+
+/// Warns about templates using a variable that nothing defines by the time they are rendered.
+pub fn get_variable_diagnostics(
+    symbols: &SymbolTable,
+    externals: &ExternalVariables,
+    positions: Positions,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut diagnostics = vec![];
+    for reference in &symbols.references {
+        let name = reference.value.as_str();
+        let line = reference.span.start.line;
+        if externals.contains(name)
+            || symbols
+                .visible_at(line)
+                .any(|variable| variable.name == name)
+        {
+            continue;
+        }
+        let message = match symbols.find(name).next() {
+            Some(variable) if variable.origin == Origin::Capture => format!(
+                "Variable `{}` is used before it is captured at line {}",
+                name,
+                variable.span.start.line + 1
+            ),
+            Some(variable) => format!(
+                "Variable `{}` is used before it is defined at line {}",
+                name,
+                variable.span.start.line + 1
+            ),
+            None => format!("Undefined variable `{}`", name),
+        };
+        diagnostics.push(lsp_types::Diagnostic {
+            severity: Some(lsp_types::DiagnosticSeverity::WARNING),
+            ..make_diagnostic(positions.range(reference.span), message)
+        });
+    }
+    diagnostics
+}
+
+/// Hurl accepts any method made of uppercase letters, not only the standard ones.
+fn check_method(
+    method: &Spanned<String>,
+    diagnostics: &mut Vec<lsp_types::Diagnostic>,
+    positions: Positions,
+) {
+    let name = method.value.as_str();
+    if name.chars().all(|c| c.is_ascii_uppercase()) {
+        return;
+    }
+    let message = if name.chars().all(|c| c.is_ascii_alphabetic()) {
+        format!(
+            "HTTP method must be uppercase, did you mean `{}`?",
+            name.to_ascii_uppercase()
+        )
+    } else {
+        format!(
+            "Invalid HTTP method `{}`, expected uppercase letters only",
+            name
+        )
+    };
+    diagnostics.push(make_diagnostic(positions.range(method.span), message));
+}
+
+fn check_response(
+    response: &Response,
+    diagnostics: &mut Vec<lsp_types::Diagnostic>,
+    positions: Positions,
+) {
+    let version = response.version.value.as_str();
+    if !VERSIONS.iter().any(|(known, _)| *known == version) {
+        let versions: Vec<&str> = VERSIONS.iter().map(|(version, _)| *version).collect();
+        diagnostics.push(make_diagnostic(
+            positions.range(response.version.span),
+            format!(
+                "Unknown HTTP version `{}`, expected one of {}",
+                version,
+                versions.join(", ")
+            ),
+        ));
+    }
+    // A missing status is reported by the parser.
+    let status = response.status.value.as_str();
+    if status.is_empty() || status == "*" {
+        return;
+    }
+    let message = match status.parse::<u16>() {
+        Ok(code) if (100..=599).contains(&code) => return,
+        Ok(code) => format!(
+            "Status code {} is out of range, expected a number from 100 to 599 or `*`",
+            code
+        ),
+        Err(_) => format!(
+            "Invalid status code `{}`, expected a number from 100 to 599 or `*`",
+            status
+        ),
+    };
+    diagnostics.push(make_diagnostic(
+        positions.range(response.status.span),
+        message,
+    ));
+}
+
+/// Request sections go before the response line, response sections after it, and a section
+/// appears at most once in an entry.
+fn check_sections(
+    entry: &Entry,
+    diagnostics: &mut Vec<lsp_types::Diagnostic>,
+    positions: Positions,
+) {
+    let request = entry
+        .request
+        .sections
+        .iter()
+        .map(|section| (section, false));
+    let response = entry
+        .response
+        .iter()
+        .flat_map(|response| response.sections.iter().map(|section| (section, true)));
+    let mut seen = vec![];
+    for (section, in_response) in request.chain(response) {
+        let range = positions.range(section.name.span);
+        let name = &section.name.value;
+        if section.kind.is_response_section() && !in_response {
+            diagnostics.push(make_diagnostic(
+                range,
+                format!(
+                    "[{}] is a response section and must come after the `HTTP` status line",
+                    name
+                ),
+            ));
+        } else if !section.kind.is_response_section() && in_response {
+            diagnostics.push(make_diagnostic(
+                range,
+                format!(
+                    "[{}] is a request section and must come before the `HTTP` status line",
+                    name
+                ),
+            ));
+        }
+        if seen.contains(&section.kind) {
+            diagnostics.push(make_diagnostic(
+                range,
+                format!("Duplicate [{}] section", name),
+            ));
+        } else {
+            seen.push(section.kind);
+        }
+    }
+}
+
+fn check_option(
+    option: &KeyValue,
+    diagnostics: &mut Vec<lsp_types::Diagnostic>,
+    positions: Positions,
+) {
+    let name = option.key.value.as_str();
+    let Some((_, expected)) = OPTIONS.iter().find(|(option, _)| *option == name) else {
+        diagnostics.push(make_diagnostic(
+            positions.range(option.key.span),
+            format!("Unknown option `{}`", name),
+        ));
+        return;
+    };
+    if !expected.accepts(&option.value.value) {
+        let span = if option.value.value.is_empty() {
+            option.span
+        } else {
+            option.value.span
+        };
+        diagnostics.push(make_diagnostic(
+            positions.range(span),
+            format!("`{}` option expects {}", name, expected.description()),
+        ));
+    }
+}
+
+fn check_query(query: &Query, diagnostics: &mut Vec<lsp_types::Diagnostic>, positions: Positions) {
+    let name = query.name.value.as_str();
+    let Some((_, expected)) = QUERIES.iter().find(|(query, _)| *query == name) else {
+        diagnostics.push(make_diagnostic(
+            positions.range(query.name.span),
+            format!("Unknown query `{}`", name),
+        ));
+        return;
+    };
+    match (expected, &query.argument) {
+        (QueryArgument::None, None) => {}
+        (QueryArgument::None, Some(argument)) => diagnostics.push(make_diagnostic(
+            positions.range(argument.span),
+            format!("`{}` query takes no argument", name),
+        )),
+        (QueryArgument::Quoted(noun), None) => diagnostics.push(make_diagnostic(
+            positions.range(query.name.span),
+            format!("`{}` query expects a quoted {}", name, noun),
+        )),
+        (QueryArgument::Quoted(noun), Some(argument)) => match &argument.value {
+            Value::String(field) if name == "certificate" => {
+                if !CERTIFICATE_FIELDS.contains(&field.as_str()) {
+                    diagnostics.push(make_diagnostic(
+                        positions.range(argument.span),
+                        format!(
+                            "Unknown certificate field, expected one of {}",
+                            CERTIFICATE_FIELDS.join(", ")
+                        ),
+                    ));
+                }
+            }
+            Value::String(_) => {}
+            _ => diagnostics.push(make_diagnostic(
+                positions.range(argument.span),
+                format!("`{}` query expects a quoted {}", name, noun),
+            )),
+        },
+        (QueryArgument::Pattern, None) => diagnostics.push(make_diagnostic(
+            positions.range(query.name.span),
+            format!("`{}` query expects a quoted pattern or a /regex/", name),
+        )),
+        (QueryArgument::Pattern, Some(_)) => {}
+    }
+}
+
+fn check_filter(
+    filter: &Filter,
+    diagnostics: &mut Vec<lsp_types::Diagnostic>,
+    positions: Positions,
+) {
+    let name = filter.name.value.as_str();
+    // The parser only builds filters with a known name.
+    let Some(expected) = grammar::filter_arguments(name) else {
+        return;
+    };
+    for (argument, expected) in filter.arguments.iter().zip(expected) {
+        if !expected.accepts(&argument.value) {
+            diagnostics.push(make_diagnostic(
+                positions.range(argument.span),
+                format!("`{}` filter expects {}", name, expected.description()),
+            ));
+        }
+    }
+    if filter.arguments.len() < expected.len() {
+        let missing: Vec<String> = expected[filter.arguments.len()..]
+            .iter()
+            .map(|argument| argument.description())
+            .collect();
+        diagnostics.push(make_diagnostic(
+            positions.range(filter.span),
+            format!("`{}` filter expects {}", name, missing.join(" and ")),
+        ));
+    } else if let (Some(first), Some(last)) = (
+        filter.arguments.get(expected.len()),
+        filter.arguments.last(),
+    ) {
+        let message = match expected.len() {
+            0 => format!("`{}` filter takes no argument", name),
+            1 => format!("`{}` filter takes 1 argument", name),
+            count => format!("`{}` filter takes {} arguments", name, count),
+        };
+        diagnostics.push(make_diagnostic(
+            positions.range(Span::new(first.span.start, last.span.end)),
+            message,
+        ));
+    }
+}
+
+fn check_predicate(
+    predicate: &Predicate,
+    diagnostics: &mut Vec<lsp_types::Diagnostic>,
+    positions: Positions,
+) {
+    let name = predicate.name.value.as_str();
+    if name == "not" {
+        diagnostics.push(make_diagnostic(
+            positions.range(predicate.name.span),
+            "`not` can only be used once, before the predicate".to_string(),
+        ));
+        return;
+    }
+    let Some((_, expected)) = PREDICATES.iter().find(|(predicate, _)| *predicate == name) else {
+        let message = if predicate.not.is_some() && grammar::filter_arguments(name).is_some() {
+            format!("Filter `{}` must come before `not`", name)
+        } else {
+            format!("Unknown predicate `{}`", name)
+        };
+        diagnostics.push(make_diagnostic(
+            positions.range(predicate.name.span),
+            message,
+        ));
+        return;
+    };
+    let message = format!("`{}` predicate expects {}", name, expected.description());
+    match (expected, &predicate.value) {
+        (PredicateValue::None, None) => {}
+        (PredicateValue::None, Some(value)) => diagnostics.push(make_diagnostic(
+            positions.range(value.span),
+            format!("`{}` predicate takes no value", name),
+        )),
+        (_, None) => diagnostics.push(make_diagnostic(
+            positions.range(predicate.name.span),
+            message,
+        )),
+        (_, Some(value)) if value.value == Value::Word("not".to_string()) => {
+            diagnostics.push(make_diagnostic(
+                positions.range(value.span),
+                "`not` must come before the predicate".to_string(),
+            ))
+        }
+        (expected, Some(value)) => {
+            if !expected.accepts(&value.value) {
+                diagnostics.push(make_diagnostic(positions.range(value.span), message));
+            }
+        }
+    }
+}
+
+pub fn syntax_error(error: &ParseError, positions: Positions) -> lsp_types::Diagnostic {
+    make_diagnostic(positions.range(error.span), error.message.clone())
+}
+
+fn make_diagnostic(range: lsp_types::Range, message: String) -> lsp_types::Diagnostic {
+    lsp_types::Diagnostic {
+        range,
+        severity: Some(lsp_types::DiagnosticSeverity::ERROR),
+        source: Some("LSP".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{Document, PositionEncoding};
+
+    fn messages(text: &str) -> Vec<(u32, String)> {
+        Document::new(text, 0, PositionEncoding::Utf16)
+            .diagnostics(&ExternalVariables::default())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message))
+            .collect()
+    }
+
+    #[test]
+    fn lowercase_method_after_a_section() {
+        let entries = [
+            "GET https://a.org\nHTTP 200\n[Asserts]\nstatus == 200\n",
+            "GET https://a.org\n[Options]\nverbose: true\n",
+        ];
+        for entry in entries {
+            let text = format!("{}\npost https://b.org\nHTTP 200\n", entry);
+            let line = entry.lines().count() + 1;
+            assert_eq!(
+                messages(&text),
+                [(
+                    line.try_into().unwrap(),
+                    "HTTP method must be uppercase, did you mean `POST`?".to_string()
+                )]
+            );
+        }
+    }
+
+    #[test]
+    fn ranges_in_utf16() {
+        let document = Document::new("GET https://a/😀 {{x}}\n", 0, PositionEncoding::Utf16);
+        let diagnostics = document.diagnostics(&ExternalVariables::default());
+        assert_eq!(
+            diagnostics[0].range,
+            lsp_types::Range::new(
+                lsp_types::Position::new(0, 19),
+                lsp_types::Position::new(0, 20)
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_method() {
+        assert_eq!(
+            messages("G3T https://a.org\n"),
+            [(
+                0,
+                "Invalid HTTP method `G3T`, expected uppercase letters only".to_string()
+            )]
+        );
+    }
+}
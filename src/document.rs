@@ -0,0 +1,481 @@
+use log::error;
+use ropey::{Rope, RopeSlice};
+
+use crate::ast::{HurlFile, Pos, Shift, Span};
+use crate::diagnostics;
+use crate::parser::{self, ParseError};
+use crate::symbols::{ExternalVariables, SymbolTable};
+
+/// Unit counted by the `character` of the positions the client sends.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PositionEncoding {
+    /// UTF-16 code units, the default of the protocol.
+    #[default]
+    Utf16,
+    /// Chars, as the tree counts them.
+    Utf32,
+}
+
+/// Converts between the positions of the tree, counted in chars, and those of the protocol,
+/// counted in the encoding negotiated with the client.
+#[derive(Clone, Copy)]
+pub struct Positions<'a> {
+    content: &'a Rope,
+    encoding: PositionEncoding,
+}
+
+impl<'a> Positions<'a> {
+    pub fn new(content: &'a Rope, encoding: PositionEncoding) -> Self {
+        Positions { content, encoding }
+    }
+
+    /// Position in the tree of a position sent by the client. Columns past the end of the line
+    /// are kept past it, one char per unit.
+    pub fn pos(&self, position: lsp_types::Position) -> Pos {
+        let line: usize = position.line.try_into().unwrap();
+        let character: usize = position.character.try_into().unwrap();
+        let column = match (self.encoding, self.line(line)) {
+            (PositionEncoding::Utf16, Some(chars)) => {
+                let len = chars.len_utf16_cu();
+                chars.utf16_cu_to_char(character.min(len)) + character.saturating_sub(len)
+            }
+            _ => character,
+        };
+        Pos::new(line, column)
+    }
+
+    pub fn position(&self, pos: Pos) -> lsp_types::Position {
+        let character = match (self.encoding, self.line(pos.line)) {
+            (PositionEncoding::Utf16, Some(chars)) => {
+                let len = chars.len_chars();
+                chars.char_to_utf16_cu(pos.column.min(len)) + pos.column.saturating_sub(len)
+            }
+            _ => pos.column,
+        };
+        lsp_types::Position::new(pos.line.try_into().unwrap(), character.try_into().unwrap())
+    }
+
+    pub fn range(&self, span: Span) -> lsp_types::Range {
+        lsp_types::Range::new(self.position(span.start), self.position(span.end))
+    }
+
+    /// Content of `line` without its line break, if the document has such a line.
+    fn line(&self, line: usize) -> Option<RopeSlice<'a>> {
+        if line >= self.content.len_lines() {
+            return None;
+        }
+        let chars = self.content.line(line);
+        let mut len = chars.len_chars();
+        while len > 0 && ['\n', '\r'].contains(&chars.char(len - 1)) {
+            len -= 1;
+        }
+        Some(chars.slice(..len))
+    }
+}
+
+/// An open text document along with the tree parsed from its current content.
+pub struct Document {
+    pub content: Rope,
+    pub version: i32,
+    /// Unit of the columns exchanged with the client about the document.
+    pub encoding: PositionEncoding,
+    pub tree: HurlFile,
+    pub errors: Vec<ParseError>,
+    /// Variables defined by `tree`, rebuilt after every parse.
+    pub symbols: SymbolTable,
+    /// The lines of `content` as the parser consumes them, kept in sync with every edit.
+    lines: Vec<Vec<char>>,
+    /// Last line read to parse each entry of `tree`.
+    extents: Vec<usize>,
+}
+
+impl Document {
+    pub fn new(text: &str, version: i32, encoding: PositionEncoding) -> Self {
+        let content = Rope::from_str(text);
+        let mut document = Document {
+            content,
+            version,
+            encoding,
+            tree: HurlFile::default(),
+            errors: vec![],
+            symbols: SymbolTable::default(),
+            lines: vec![],
+            extents: vec![],
+        };
+        document.parse();
+        document
+    }
+
+    fn parse(&mut self) {
+        self.lines = rope_lines(&self.content);
+        let parsed = parser::parse_entries(&self.lines, 0, |_| false);
+        self.tree.entries = parsed.entries;
+        self.extents = parsed.extents;
+        self.errors = parsed.errors;
+        self.symbols = SymbolTable::new(&self.tree, &self.lines);
+    }
+
+    /// Applies the changes of a `textDocument/didChange` notification in order. Changes for a
+    /// version that isn't newer than the stored one are rejected since applying them to the
+    /// wrong content would corrupt the document.
+    pub fn apply_changes(
+        &mut self,
+        version: i32,
+        changes: &[lsp_types::TextDocumentContentChangeEvent],
+    ) -> Result<(), String> {
+        if version <= self.version {
+            return Err(format!(
+                "Received version {} but document is already at version {}",
+                version, self.version
+            ));
+        }
+        // Lines touched by the changes, in the coordinates of the new content, and the number of
+        // lines the content grew or shrank by.
+        let mut dirty: Option<(usize, usize)> = None;
+        let mut full = false;
+        let mut delta: isize = 0;
+        for change in changes {
+            match change.range {
+                None => {
+                    self.content = Rope::from_str(change.text.as_str());
+                    full = true;
+                }
+                Some(range) => {
+                    let start = self.position_to_char(range.start);
+                    let end = self.position_to_char(range.end).max(start);
+                    let start_line = self.content.char_to_line(start);
+                    let end_line = self.content.char_to_line(end);
+                    self.content.remove(start..end);
+                    self.content.insert(start, change.text.as_str());
+                    let new_end_line = self
+                        .content
+                        .char_to_line(start + change.text.chars().count());
+
+                    if !full {
+                        self.lines.splice(
+                            start_line..=end_line,
+                            (start_line..=new_end_line).map(|line| rope_line(&self.content, line)),
+                        );
+                    }
+                    let change_delta = new_end_line as isize - end_line as isize;
+                    let map = |line: usize| {
+                        if line > end_line {
+                            line.saturating_add_signed(change_delta)
+                        } else if line >= start_line {
+                            new_end_line
+                        } else {
+                            line
+                        }
+                    };
+                    dirty = Some(match dirty {
+                        None => (start_line, new_end_line),
+                        Some((dirty_start, dirty_end)) => (
+                            map(dirty_start).min(start_line),
+                            map(dirty_end).max(new_end_line),
+                        ),
+                    });
+                    delta += change_delta;
+                }
+            }
+        }
+        self.version = version;
+
+        if !full && self.lines.len() != self.content.len_lines() {
+            error!("Line cache out of sync with the document, parsing it again");
+            full = true;
+        }
+        if full {
+            self.parse();
+        } else if let Some((dirty_start, dirty_end)) = dirty {
+            self.reparse(dirty_start, dirty_end, delta);
+        }
+        Ok(())
+    }
+
+    /// Parses again the entries that read any line of `dirty_start..=dirty_end` and reuses the
+    /// previous tree for the others. Entries after the edit are moved by `delta` lines.
+    fn reparse(&mut self, dirty_start: usize, dirty_end: usize, delta: isize) {
+        let mut old_entries = std::mem::take(&mut self.tree.entries);
+        let mut old_extents = std::mem::take(&mut self.extents);
+        let old_errors = std::mem::take(&mut self.errors);
+
+        // Lines before the edit are the same in the old and new content, so entries that didn't
+        // read past them are kept as they are.
+        let kept = old_extents
+            .iter()
+            .position(|extent| *extent >= dirty_start)
+            .unwrap_or(old_entries.len());
+        let start_line = old_entries
+            .get(kept)
+            .map(|entry| entry.span.start.line)
+            .unwrap_or(dirty_start)
+            .min(dirty_start);
+
+        // Parsing stops at the first entry after the edit that started at the same place in the
+        // previous tree: everything from there on is unchanged.
+        let old_dirty_end = dirty_end.saturating_add_signed(-delta);
+        let old_starts: Vec<usize> = old_entries
+            .iter()
+            .map(|entry| entry.span.start.line)
+            .filter(|line| *line > old_dirty_end)
+            .collect();
+        let parsed = parser::parse_entries(&self.lines, start_line, |line| {
+            line > dirty_end
+                && old_starts
+                    .binary_search(&line.saturating_add_signed(-delta))
+                    .is_ok()
+        });
+        // Nothing from the previous tree is reused when parsing reached the end of the file.
+        let old_stop_line = if parsed.stop_line < self.lines.len() {
+            parsed.stop_line.saturating_add_signed(-delta)
+        } else {
+            usize::MAX
+        };
+
+        let tail_index = old_entries
+            .iter()
+            .position(|entry| entry.span.start.line >= old_stop_line)
+            .unwrap_or(old_entries.len());
+        let mut tail = old_entries.split_off(tail_index);
+        let mut tail_extents = old_extents.split_off(tail_index);
+        tail.shift(delta);
+        for extent in tail_extents.iter_mut() {
+            *extent = extent.saturating_add_signed(delta);
+        }
+        old_entries.truncate(kept);
+        old_entries.extend(parsed.entries);
+        old_entries.extend(tail);
+        old_extents.truncate(kept);
+        old_extents.extend(parsed.extents);
+        old_extents.extend(tail_extents);
+        self.tree.entries = old_entries;
+        self.extents = old_extents;
+
+        let (head_errors, mut tail_errors): (Vec<ParseError>, Vec<ParseError>) = old_errors
+            .into_iter()
+            .filter(|error| {
+                error.span.start.line < start_line || error.span.start.line >= old_stop_line
+            })
+            .partition(|error| error.span.start.line < start_line);
+        tail_errors.shift(delta);
+        self.errors = head_errors
+            .into_iter()
+            .chain(parsed.errors)
+            .chain(tail_errors)
+            .collect();
+        self.symbols = SymbolTable::new(&self.tree, &self.lines);
+    }
+
+    pub fn diagnostics(&self, externals: &ExternalVariables) -> Vec<lsp_types::Diagnostic> {
+        let positions = self.positions();
+        let mut diagnostics: Vec<lsp_types::Diagnostic> = self
+            .errors
+            .iter()
+            .map(|error| diagnostics::syntax_error(error, positions))
+            .collect();
+        diagnostics.extend(diagnostics::get_diagnostics(&self.tree, positions));
+        diagnostics.extend(diagnostics::get_variable_diagnostics(
+            &self.symbols,
+            externals,
+            positions,
+        ));
+        diagnostics
+    }
+
+    pub fn positions(&self) -> Positions<'_> {
+        Positions::new(&self.content, self.encoding)
+    }
+
+    /// Chars of a line without its line break, empty past the end of the document.
+    pub fn line(&self, line: usize) -> &[char] {
+        self.lines.get(line).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Char index of an LSP position, clamped to the end of its line and of the document.
+    fn position_to_char(&self, position: lsp_types::Position) -> usize {
+        let pos = self.positions().pos(position);
+        match self.positions().line(pos.line) {
+            Some(chars) => self.content.line_to_char(pos.line) + pos.column.min(chars.len_chars()),
+            None => self.content.len_chars(),
+        }
+    }
+}
+
+fn rope_lines(content: &Rope) -> Vec<Vec<char>> {
+    (0..content.len_lines())
+        .map(|line| rope_line(content, line))
+        .collect()
+}
+
+/// Chars of a line without its line break.
+fn rope_line(content: &Rope, line: usize) -> Vec<char> {
+    let mut chars: Vec<char> = content.line(line).chars().collect();
+    while chars.last().is_some_and(|c| ['\n', '\r'].contains(c)) {
+        chars.pop();
+    }
+    chars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = r#"# Log in
+POST https://example.org/login
+Content-Type: application/json
+{
+    "user": "{{user}}"
+}
+HTTP 200
+[Captures]
+token: jsonpath "$.token"
+[Asserts]
+body == ```
+# not a comment {{user}}
+```
+
+GET https://example.org/items
+Authorization: Bearer {{token}}
+[QueryStringParams]
+page: 1
+HTTP 200
+<items><item/></items>
+
+POST https://example.org/query
+```sql
+SELECT * FROM items
+```
+"#;
+
+    /// Snippets typed at random places, chosen to open, close and break constructs.
+    const SNIPPETS: &[&str] = &[
+        "x",
+        " ",
+        "\n",
+        "\r\n",
+        "\r",
+        "{",
+        "}",
+        "\"",
+        "`",
+        "```",
+        "#",
+        "<a>",
+        "</a>",
+        "{{id}}",
+        "[Asserts]\n",
+        "HTTP 200\n",
+        "status == 200\n",
+        "id: jsonpath \"$.id\"\n",
+        "\nGET https://example.org\n",
+    ];
+
+    /// A xorshift generator, enough to pick reproducible edits.
+    struct Random(u64);
+
+    impl Random {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+
+        fn position(&mut self, document: &Document) -> lsp_types::Position {
+            let line = self.below(document.lines.len() + 1);
+            let character = self.below(document.line(line).len() + 2);
+            lsp_types::Position::new(line.try_into().unwrap(), character.try_into().unwrap())
+        }
+    }
+
+    #[test]
+    fn positions_in_the_negotiated_encoding() {
+        let document = Document::new("GET https://a/😀 {{x}}\r\nx", 0, PositionEncoding::Utf16);
+        let positions = document.positions();
+        let pos = Pos::new(0, 18);
+        assert_eq!(positions.position(pos), lsp_types::Position::new(0, 19));
+        assert_eq!(positions.pos(lsp_types::Position::new(0, 19)), pos);
+        // Past the end of a line or of the document, a unit stands for a char.
+        assert_eq!(
+            positions.position(Pos::new(0, 23)),
+            lsp_types::Position::new(0, 24)
+        );
+        assert_eq!(
+            positions.pos(lsp_types::Position::new(1, 3)),
+            Pos::new(1, 3)
+        );
+        assert_eq!(
+            positions.position(Pos::new(5, 2)),
+            lsp_types::Position::new(5, 2)
+        );
+
+        let document = Document::new("GET https://a/😀 {{x}}", 0, PositionEncoding::Utf32);
+        let positions = document.positions();
+        assert_eq!(positions.position(pos), lsp_types::Position::new(0, 18));
+        assert_eq!(positions.pos(lsp_types::Position::new(0, 18)), pos);
+    }
+
+    #[test]
+    fn utf16_edits() {
+        let mut document = Document::new("GET https://a/😀x\n", 0, PositionEncoding::Utf16);
+        let change = lsp_types::TextDocumentContentChangeEvent {
+            range: Some(lsp_types::Range::new(
+                lsp_types::Position::new(0, 16),
+                lsp_types::Position::new(0, 17),
+            )),
+            range_length: None,
+            text: "y".to_string(),
+        };
+        document.apply_changes(1, &[change]).unwrap();
+        assert_eq!(document.content.to_string(), "GET https://a/😀y\n");
+    }
+
+    #[test]
+    fn incremental_edits_match_a_full_parse() {
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+        let mut document = Document::new(TEXT, 0, PositionEncoding::Utf16);
+        for version in 1..=2_000 {
+            let changes: Vec<lsp_types::TextDocumentContentChangeEvent> = (0..1 + random.below(3))
+                .map(|_| {
+                    let start = random.position(&document);
+                    let end = match random.below(3) {
+                        0 => random.position(&document).max(start),
+                        _ => start,
+                    };
+                    let text = match random.below(3) {
+                        0 => "",
+                        _ => SNIPPETS[random.below(SNIPPETS.len())],
+                    };
+                    lsp_types::TextDocumentContentChangeEvent {
+                        range: Some(lsp_types::Range::new(start, end)),
+                        range_length: None,
+                        text: text.to_string(),
+                    }
+                })
+                .collect();
+            document.apply_changes(version, &changes).unwrap();
+
+            let text = document.content.to_string();
+            let expected = Document::new(&text, version, PositionEncoding::Utf16);
+            assert_eq!(document.lines, expected.lines, "lines of {:?}", text);
+            assert_eq!(
+                format!("{:?}", document.tree),
+                format!("{:?}", expected.tree),
+                "tree of {:?}",
+                text
+            );
+            assert_eq!(
+                format!("{:?}", document.errors),
+                format!("{:?}", expected.errors),
+                "errors of {:?}",
+                text
+            );
+            assert_eq!(
+                format!("{:?}", document.symbols),
+                format!("{:?}", expected.symbols),
+                "symbols of {:?}",
+                text
+            );
+        }
+    }
+}
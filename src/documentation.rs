@@ -0,0 +1,111 @@
+use phf::phf_map;
+
+pub const DOCUMENTATION_MAP: phf::Map<&'static str, &'static str> = phf_map! {
+    "jsonpath" => include_str!("static_documentation/jsonpath.md"),
+    "status" => include_str!("static_documentation/status.md"),
+    "url" => include_str!("static_documentation/url.md"),
+    "header" => include_str!("static_documentation/header.md"),
+    "cookie" => include_str!("static_documentation/cookie.md"),
+    "body" => include_str!("static_documentation/body.md"),
+    "xpath" => include_str!("static_documentation/xpath.md"),
+    "regex" => include_str!("static_documentation/regex.md"),
+    "variable" => include_str!("static_documentation/variable.md"),
+    "duration" => include_str!("static_documentation/duration.md"),
+    "sha256" => include_str!("static_documentation/sha256.md"),
+    "md5" => include_str!("static_documentation/md5.md"),
+    "bytes" => include_str!("static_documentation/bytes.md"),
+    "version" => include_str!("static_documentation/version.md"),
+    "certificate" => include_str!("static_documentation/certificate.md"),
+    "ip" => include_str!("static_documentation/ip.md"),
+    "redirects" => include_str!("static_documentation/redirects.md"),
+    "==" => include_str!("static_documentation/equal.md"),
+    "!=" => include_str!("static_documentation/equal.md"),
+    ">" => include_str!("static_documentation/compare.md"),
+    ">=" => include_str!("static_documentation/compare.md"),
+    "<" => include_str!("static_documentation/compare.md"),
+    "<=" => include_str!("static_documentation/compare.md"),
+    "startsWith" => include_str!("static_documentation/startsWith.md"),
+    "endsWith" => include_str!("static_documentation/endsWith.md"),
+    "contains" => include_str!("static_documentation/contains.md"),
+    "includes" => include_str!("static_documentation/includes.md"),
+    "matches" => include_str!("static_documentation/matches.md"),
+    "exists" => include_str!("static_documentation/exists.md"),
+    "isBoolean" => include_str!("static_documentation/type_predicates.md"),
+    "isCollection" => include_str!("static_documentation/type_predicates.md"),
+    "isDate" => include_str!("static_documentation/type_predicates.md"),
+    "isEmpty" => include_str!("static_documentation/type_predicates.md"),
+    "isFloat" => include_str!("static_documentation/type_predicates.md"),
+    "isInteger" => include_str!("static_documentation/type_predicates.md"),
+    "isIpv4" => include_str!("static_documentation/type_predicates.md"),
+    "isIpv6" => include_str!("static_documentation/type_predicates.md"),
+    "isIsoDate" => include_str!("static_documentation/type_predicates.md"),
+    "isNumber" => include_str!("static_documentation/type_predicates.md"),
+    "isString" => include_str!("static_documentation/type_predicates.md"),
+    "isUuid" => include_str!("static_documentation/type_predicates.md"),
+    "not" => include_str!("static_documentation/not.md"),
+    "filters/base64Decode" => include_str!("static_documentation/filters/base64Decode.md"),
+    "filters/base64Encode" => include_str!("static_documentation/filters/base64Encode.md"),
+    "filters/base64UrlSafeDecode" => include_str!("static_documentation/filters/base64UrlSafeDecode.md"),
+    "filters/base64UrlSafeEncode" => include_str!("static_documentation/filters/base64UrlSafeEncode.md"),
+    "filters/count" => include_str!("static_documentation/filters/count.md"),
+    "filters/daysAfterNow" => include_str!("static_documentation/filters/daysAfterNow.md"),
+    "filters/daysBeforeNow" => include_str!("static_documentation/filters/daysBeforeNow.md"),
+    "filters/decode" => include_str!("static_documentation/filters/decode.md"),
+    "filters/first" => include_str!("static_documentation/filters/first.md"),
+    "filters/format" => include_str!("static_documentation/filters/format.md"),
+    "filters/htmlEscape" => include_str!("static_documentation/filters/htmlEscape.md"),
+    "filters/htmlUnescape" => include_str!("static_documentation/filters/htmlUnescape.md"),
+    "filters/jsonpath" => include_str!("static_documentation/filters/jsonpath.md"),
+    "filters/last" => include_str!("static_documentation/filters/last.md"),
+    "filters/location" => include_str!("static_documentation/filters/location.md"),
+    "filters/nth" => include_str!("static_documentation/filters/nth.md"),
+    "filters/regex" => include_str!("static_documentation/filters/regex.md"),
+    "filters/replace" => include_str!("static_documentation/filters/replace.md"),
+    "filters/split" => include_str!("static_documentation/filters/split.md"),
+    "filters/toDate" => include_str!("static_documentation/filters/toDate.md"),
+    "filters/toFloat" => include_str!("static_documentation/filters/toFloat.md"),
+    "filters/toHex" => include_str!("static_documentation/filters/toHex.md"),
+    "filters/toInt" => include_str!("static_documentation/filters/toInt.md"),
+    "filters/toString" => include_str!("static_documentation/filters/toString.md"),
+    "filters/urlDecode" => include_str!("static_documentation/filters/urlDecode.md"),
+    "filters/urlEncode" => include_str!("static_documentation/filters/urlEncode.md"),
+    "filters/urlQueryParam" => include_str!("static_documentation/filters/urlQueryParam.md"),
+    "filters/utf8Decode" => include_str!("static_documentation/filters/utf8Decode.md"),
+    "filters/utf8Encode" => include_str!("static_documentation/filters/utf8Encode.md"),
+    "filters/xpath" => include_str!("static_documentation/filters/xpath.md"),
+    "options/aws-sigv4" => include_str!("static_documentation/options/aws-sigv4.md"),
+    "options/cacert" => include_str!("static_documentation/options/cacert.md"),
+    "options/cert" => include_str!("static_documentation/options/cert.md"),
+    "options/compressed" => include_str!("static_documentation/options/compressed.md"),
+    "options/connect-timeout" => include_str!("static_documentation/options/connect-timeout.md"),
+    "options/delay" => include_str!("static_documentation/options/delay.md"),
+    "options/http1.0" => include_str!("static_documentation/options/http1.0.md"),
+    "options/http1.1" => include_str!("static_documentation/options/http1.1.md"),
+    "options/http2" => include_str!("static_documentation/options/http2.md"),
+    "options/http3" => include_str!("static_documentation/options/http3.md"),
+    "options/insecure" => include_str!("static_documentation/options/insecure.md"),
+    "options/ipv4" => include_str!("static_documentation/options/ipv4.md"),
+    "options/ipv6" => include_str!("static_documentation/options/ipv6.md"),
+    "options/key" => include_str!("static_documentation/options/key.md"),
+    "options/limit-rate" => include_str!("static_documentation/options/limit-rate.md"),
+    "options/location" => include_str!("static_documentation/options/location.md"),
+    "options/location-trusted" => include_str!("static_documentation/options/location-trusted.md"),
+    "options/max-redirs" => include_str!("static_documentation/options/max-redirs.md"),
+    "options/max-time" => include_str!("static_documentation/options/max-time.md"),
+    "options/netrc" => include_str!("static_documentation/options/netrc.md"),
+    "options/netrc-file" => include_str!("static_documentation/options/netrc-file.md"),
+    "options/netrc-optional" => include_str!("static_documentation/options/netrc-optional.md"),
+    "options/output" => include_str!("static_documentation/options/output.md"),
+    "options/path-as-is" => include_str!("static_documentation/options/path-as-is.md"),
+    "options/proxy" => include_str!("static_documentation/options/proxy.md"),
+    "options/repeat" => include_str!("static_documentation/options/repeat.md"),
+    "options/resolve" => include_str!("static_documentation/options/resolve.md"),
+    "options/retry" => include_str!("static_documentation/options/retry.md"),
+    "options/retry-interval" => include_str!("static_documentation/options/retry-interval.md"),
+    "options/skip" => include_str!("static_documentation/options/skip.md"),
+    "options/unix-socket" => include_str!("static_documentation/options/unix-socket.md"),
+    "options/user" => include_str!("static_documentation/options/user.md"),
+    "options/variable" => include_str!("static_documentation/options/variable.md"),
+    "options/verbose" => include_str!("static_documentation/options/verbose.md"),
+    "options/very-verbose" => include_str!("static_documentation/options/very-verbose.md"),
+};
@@ -0,0 +1,44 @@
+use crate::ast::{Body, BodyKind, HurlFile, Span};
+
+/// Folds of each entry, each section, each body spanning several lines (JSON, XML and multiline
+/// strings) and each ``` predicate value. Folding an entry leaves its request line visible.
+pub fn get_folding_ranges(tree: &HurlFile) -> Vec<lsp_types::FoldingRange> {
+    let mut ranges = vec![];
+    for entry in &tree.entries {
+        ranges.extend(make_range(entry.span));
+        ranges.extend(
+            entry
+                .sections()
+                .filter_map(|section| make_range(section.span)),
+        );
+    }
+    ranges.extend(
+        tree.bodies()
+            .filter(|body| is_block(body))
+            .filter_map(|body| make_range(body.span)),
+    );
+    ranges.extend(
+        tree.multiline_values()
+            .filter_map(|value| make_range(value.span)),
+    );
+    ranges
+}
+
+fn is_block(body: &Body) -> bool {
+    matches!(
+        body.kind,
+        BodyKind::Json | BodyKind::Xml | BodyKind::MultilineString
+    )
+}
+
+fn make_range(span: Span) -> Option<lsp_types::FoldingRange> {
+    if span.end.line <= span.start.line {
+        return None;
+    }
+    Some(lsp_types::FoldingRange {
+        start_line: span.start.line.try_into().unwrap(),
+        end_line: span.end.line.try_into().unwrap(),
+        kind: Some(lsp_types::FoldingRangeKind::Region),
+        ..Default::default()
+    })
+}
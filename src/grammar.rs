@@ -0,0 +1,596 @@
+// Static description of the Hurl grammar elements the language features know about: methods,
+// response versions and status codes, sections, options, and the queries, filters and
+// predicates with what each of them expects after its name.
+
+use crate::ast::Value;
+
+pub const METHODS: &[&str] = &[
+    "GET", "HEAD", "POST", "PUT", "DELETE", "CONNECT", "OPTIONS", "TRACE", "PATCH",
+];
+
+pub const VERSIONS: &[(&str, &str)] = &[
+    ("HTTP", "Any HTTP version"),
+    ("HTTP/1.0", "HTTP/1.0"),
+    ("HTTP/1.1", "HTTP/1.1"),
+    ("HTTP/2", "HTTP/2"),
+    ("HTTP/3", "HTTP/3"),
+];
+
+/// Status codes with their reason phrase and meaning. Any code from 100 to 599 is accepted in a
+/// response line, these are the standard ones offered and documented.
+pub const STATUS_CODES: &[(&str, &str, &str)] = &[
+    ("*", "Any status", "Any status code, when the status is not checked."),
+    (
+        "100",
+        "Continue",
+        "The server has received the request headers and the client should proceed to send the request body.",
+    ),
+    (
+        "101",
+        "Switching Protocols",
+        "The server is switching to the protocol requested by the client in the `Upgrade` header.",
+    ),
+    (
+        "103",
+        "Early Hints",
+        "The server sends some response headers before the final response, typically so the client can preload resources.",
+    ),
+    (
+        "200",
+        "OK",
+        "The request succeeded.",
+    ),
+    (
+        "201",
+        "Created",
+        "The request succeeded and a new resource was created as a result.",
+    ),
+    (
+        "202",
+        "Accepted",
+        "The request has been received but not yet acted upon.",
+    ),
+    (
+        "203",
+        "Non-Authoritative Information",
+        "The returned metadata comes from a local or third-party copy rather than from the origin server.",
+    ),
+    (
+        "204",
+        "No Content",
+        "The request succeeded and there is no content to send in the response body.",
+    ),
+    (
+        "205",
+        "Reset Content",
+        "The request succeeded and the client should reset the document that sent it.",
+    ),
+    (
+        "206",
+        "Partial Content",
+        "The response body contains only the part of the resource requested by the `Range` header.",
+    ),
+    (
+        "300",
+        "Multiple Choices",
+        "The request has more than one possible response and the client should choose one of them.",
+    ),
+    (
+        "301",
+        "Moved Permanently",
+        "The resource has moved permanently to the URL given by the `Location` header.",
+    ),
+    (
+        "302",
+        "Found",
+        "The resource is temporarily available at the URL given by the `Location` header.",
+    ),
+    (
+        "303",
+        "See Other",
+        "The client should get the response at the URL given by the `Location` header with a GET request.",
+    ),
+    (
+        "304",
+        "Not Modified",
+        "The resource has not been modified since the version given by the conditional headers of the request.",
+    ),
+    (
+        "307",
+        "Temporary Redirect",
+        "The resource is temporarily at another URL and the request must be repeated there with the same method.",
+    ),
+    (
+        "308",
+        "Permanent Redirect",
+        "The resource is permanently at another URL and the request must be repeated there with the same method.",
+    ),
+    (
+        "400",
+        "Bad Request",
+        "The server cannot process the request because of a client error such as malformed syntax.",
+    ),
+    (
+        "401",
+        "Unauthorized",
+        "The request lacks valid authentication credentials for the resource.",
+    ),
+    (
+        "402",
+        "Payment Required",
+        "Reserved for future use, sometimes used by APIs for payment or quota errors.",
+    ),
+    (
+        "403",
+        "Forbidden",
+        "The client is known to the server but does not have access rights to the resource.",
+    ),
+    (
+        "404",
+        "Not Found",
+        "The server cannot find the requested resource.",
+    ),
+    (
+        "405",
+        "Method Not Allowed",
+        "The request method is not supported by the target resource.",
+    ),
+    (
+        "406",
+        "Not Acceptable",
+        "No representation of the resource matches the `Accept` headers of the request.",
+    ),
+    (
+        "407",
+        "Proxy Authentication Required",
+        "The client must authenticate with the proxy.",
+    ),
+    (
+        "408",
+        "Request Timeout",
+        "The server timed out waiting for the request.",
+    ),
+    (
+        "409",
+        "Conflict",
+        "The request conflicts with the current state of the resource.",
+    ),
+    (
+        "410",
+        "Gone",
+        "The resource has been permanently deleted from the server.",
+    ),
+    (
+        "411",
+        "Length Required",
+        "The server requires a `Content-Length` header.",
+    ),
+    (
+        "412",
+        "Precondition Failed",
+        "The preconditions given by the conditional headers of the request are not met.",
+    ),
+    (
+        "413",
+        "Content Too Large",
+        "The request body is larger than the server is willing to process.",
+    ),
+    (
+        "414",
+        "URI Too Long",
+        "The requested URI is longer than the server is willing to interpret.",
+    ),
+    (
+        "415",
+        "Unsupported Media Type",
+        "The media type of the request body is not supported by the server.",
+    ),
+    (
+        "416",
+        "Range Not Satisfiable",
+        "The range given by the `Range` header cannot be fulfilled.",
+    ),
+    (
+        "417",
+        "Expectation Failed",
+        "The expectation given by the `Expect` header cannot be met.",
+    ),
+    (
+        "418",
+        "I'm a teapot",
+        "The server refuses to brew coffee because it is, permanently, a teapot.",
+    ),
+    (
+        "421",
+        "Misdirected Request",
+        "The request was directed at a server that is not able to produce a response.",
+    ),
+    (
+        "422",
+        "Unprocessable Content",
+        "The request is well-formed but the server is unable to process its content.",
+    ),
+    (
+        "423",
+        "Locked",
+        "The resource being accessed is locked.",
+    ),
+    (
+        "425",
+        "Too Early",
+        "The server is unwilling to process a request that might be replayed.",
+    ),
+    (
+        "426",
+        "Upgrade Required",
+        "The client should switch to the protocol given by the `Upgrade` header.",
+    ),
+    (
+        "428",
+        "Precondition Required",
+        "The server requires the request to be conditional.",
+    ),
+    (
+        "429",
+        "Too Many Requests",
+        "The client has sent too many requests in a given amount of time.",
+    ),
+    (
+        "431",
+        "Request Header Fields Too Large",
+        "The headers of the request are too large for the server to process.",
+    ),
+    (
+        "451",
+        "Unavailable For Legal Reasons",
+        "The resource cannot be provided for legal reasons.",
+    ),
+    (
+        "500",
+        "Internal Server Error",
+        "The server encountered an unexpected condition that prevented it from fulfilling the request.",
+    ),
+    (
+        "501",
+        "Not Implemented",
+        "The request method is not supported by the server.",
+    ),
+    (
+        "502",
+        "Bad Gateway",
+        "The server, acting as a gateway, received an invalid response from the upstream server.",
+    ),
+    (
+        "503",
+        "Service Unavailable",
+        "The server is not ready to handle the request, usually because it is down for maintenance or overloaded.",
+    ),
+    (
+        "504",
+        "Gateway Timeout",
+        "The server, acting as a gateway, did not get a response from the upstream server in time.",
+    ),
+    (
+        "505",
+        "HTTP Version Not Supported",
+        "The HTTP version used in the request is not supported by the server.",
+    ),
+    (
+        "511",
+        "Network Authentication Required",
+        "The client needs to authenticate to gain network access.",
+    ),
+];
+
+/// Section names, each followed by its short alias when it has one.
+pub const SECTIONS: &[&str] = &[
+    "QueryStringParams",
+    "Query",
+    "FormParams",
+    "Form",
+    "MultipartFormData",
+    "Multipart",
+    "Cookies",
+    "BasicAuth",
+    "Options",
+    "Captures",
+    "Asserts",
+];
+
+/// Type of the value of an `[Options]` entry.
+#[derive(Clone, Copy)]
+pub enum OptionValue {
+    Boolean,
+    Integer,
+    /// An integer in milliseconds, or followed by a `ms`, `s` or `m` unit.
+    Duration,
+    Path,
+    String,
+    /// A `name=value` variable definition.
+    Variable,
+}
+
+impl OptionValue {
+    pub fn description(self) -> &'static str {
+        match self {
+            OptionValue::Boolean => "a boolean (`true` or `false`)",
+            OptionValue::Integer => "an integer",
+            OptionValue::Duration => "a duration such as `500`, `500ms`, `2s` or `1m`",
+            OptionValue::Path => "a file path",
+            OptionValue::String => "a value",
+            OptionValue::Variable => "a variable definition such as `name=value`",
+        }
+    }
+
+    pub fn accepts(self, value: &str) -> bool {
+        // A template may expand to anything.
+        if value.contains("{{") {
+            return true;
+        }
+        match self {
+            OptionValue::Boolean => value == "true" || value == "false",
+            OptionValue::Integer => value.parse::<i64>().is_ok(),
+            OptionValue::Duration => {
+                let number = ["ms", "s", "m"]
+                    .iter()
+                    .find_map(|unit| value.strip_suffix(unit))
+                    .unwrap_or(value);
+                number.parse::<u64>().is_ok()
+            }
+            OptionValue::Path | OptionValue::String => !value.is_empty(),
+            OptionValue::Variable => value
+                .split_once('=')
+                .is_some_and(|(name, _)| is_variable_name(name.trim())),
+        }
+    }
+}
+
+/// Whether `name` can name a variable: a letter or `_` followed by letters, digits, `_` or `-`.
+pub fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Error for a `name` rejected by `is_variable_name`.
+pub fn invalid_variable_name(name: &str) -> String {
+    format!(
+        "Invalid variable name `{}`, expected a letter or `_` followed by letters, digits, `_` or `-`",
+        name
+    )
+}
+
+/// Functions a template can call instead of reading a variable.
+pub const FUNCTIONS: &[&str] = &["newDate", "newUuid"];
+
+pub const OPTIONS: &[(&str, OptionValue)] = &[
+    ("aws-sigv4", OptionValue::String),
+    ("cacert", OptionValue::Path),
+    ("cert", OptionValue::Path),
+    ("compressed", OptionValue::Boolean),
+    ("connect-timeout", OptionValue::Duration),
+    ("delay", OptionValue::Duration),
+    ("http1.0", OptionValue::Boolean),
+    ("http1.1", OptionValue::Boolean),
+    ("http2", OptionValue::Boolean),
+    ("http3", OptionValue::Boolean),
+    ("insecure", OptionValue::Boolean),
+    ("ipv4", OptionValue::Boolean),
+    ("ipv6", OptionValue::Boolean),
+    ("key", OptionValue::Path),
+    ("limit-rate", OptionValue::Integer),
+    ("location", OptionValue::Boolean),
+    ("location-trusted", OptionValue::Boolean),
+    ("max-redirs", OptionValue::Integer),
+    ("max-time", OptionValue::Duration),
+    ("netrc", OptionValue::Boolean),
+    ("netrc-file", OptionValue::Path),
+    ("netrc-optional", OptionValue::Boolean),
+    ("output", OptionValue::Path),
+    ("path-as-is", OptionValue::Boolean),
+    ("proxy", OptionValue::String),
+    ("repeat", OptionValue::Integer),
+    ("resolve", OptionValue::String),
+    ("retry", OptionValue::Integer),
+    ("retry-interval", OptionValue::Duration),
+    ("skip", OptionValue::Boolean),
+    ("unix-socket", OptionValue::Path),
+    ("user", OptionValue::String),
+    ("variable", OptionValue::Variable),
+    ("verbose", OptionValue::Boolean),
+    ("very-verbose", OptionValue::Boolean),
+];
+
+/// Argument a query expects after its name.
+#[derive(Clone, Copy)]
+pub enum QueryArgument {
+    None,
+    /// A quoted string, described by the given noun in messages.
+    Quoted(&'static str),
+    /// A quoted string or a `/regex/`.
+    Pattern,
+}
+
+pub const QUERIES: &[(&str, QueryArgument)] = &[
+    ("status", QueryArgument::None),
+    ("version", QueryArgument::None),
+    ("url", QueryArgument::None),
+    ("header", QueryArgument::Quoted("header name")),
+    ("certificate", QueryArgument::Quoted("certificate field")),
+    ("cookie", QueryArgument::Quoted("cookie name")),
+    ("body", QueryArgument::None),
+    ("xpath", QueryArgument::Quoted("XPath expression")),
+    ("jsonpath", QueryArgument::Quoted("JSONPath expression")),
+    ("regex", QueryArgument::Pattern),
+    ("variable", QueryArgument::Quoted("variable name")),
+    ("duration", QueryArgument::None),
+    ("bytes", QueryArgument::None),
+    ("sha256", QueryArgument::None),
+    ("md5", QueryArgument::None),
+    ("ip", QueryArgument::None),
+    ("redirects", QueryArgument::None),
+];
+
+pub const CERTIFICATE_FIELDS: &[&str] = &[
+    "Subject",
+    "Issuer",
+    "Start-Date",
+    "Expire-Date",
+    "Serial-Number",
+];
+
+/// Argument a filter expects after its name.
+#[derive(Clone, Copy)]
+pub enum FilterArgument {
+    /// An integer, described by the given noun in messages.
+    Integer(&'static str),
+    /// A quoted string, described by the given noun in messages.
+    Quoted(&'static str),
+    /// A quoted string or a `/regex/`.
+    Pattern,
+}
+
+impl FilterArgument {
+    pub fn description(self) -> String {
+        match self {
+            FilterArgument::Integer(noun) => format!("an integer {}", noun),
+            FilterArgument::Quoted(noun) => format!("a quoted {}", noun),
+            FilterArgument::Pattern => "a quoted string or a /regex/".to_string(),
+        }
+    }
+
+    pub fn accepts(self, value: &Value) -> bool {
+        match (self, value) {
+            (_, Value::Template(_)) => true,
+            (FilterArgument::Integer(_), value) => matches!(value, Value::Integer(_)),
+            (FilterArgument::Quoted(_), value) => matches!(value, Value::String(_)),
+            (FilterArgument::Pattern, value) => {
+                matches!(value, Value::String(_) | Value::Regex(_))
+            }
+        }
+    }
+}
+
+pub const FILTERS: &[(&str, &[FilterArgument])] = &[
+    ("base64Decode", &[]),
+    ("base64Encode", &[]),
+    ("base64UrlSafeDecode", &[]),
+    ("base64UrlSafeEncode", &[]),
+    ("count", &[]),
+    ("daysAfterNow", &[]),
+    ("daysBeforeNow", &[]),
+    ("decode", &[FilterArgument::Quoted("encoding")]),
+    ("first", &[]),
+    ("format", &[FilterArgument::Quoted("date format")]),
+    ("htmlEscape", &[]),
+    ("htmlUnescape", &[]),
+    ("jsonpath", &[FilterArgument::Quoted("JSONPath expression")]),
+    ("last", &[]),
+    ("location", &[]),
+    ("nth", &[FilterArgument::Integer("index")]),
+    ("regex", &[FilterArgument::Pattern]),
+    (
+        "replace",
+        &[
+            FilterArgument::Pattern,
+            FilterArgument::Quoted("replacement"),
+        ],
+    ),
+    ("split", &[FilterArgument::Quoted("separator")]),
+    ("toDate", &[FilterArgument::Quoted("date format")]),
+    ("toFloat", &[]),
+    ("toHex", &[]),
+    ("toInt", &[]),
+    ("toString", &[]),
+    ("urlDecode", &[]),
+    ("urlEncode", &[]),
+    ("urlQueryParam", &[FilterArgument::Quoted("parameter name")]),
+    ("utf8Decode", &[]),
+    ("utf8Encode", &[]),
+    ("xpath", &[FilterArgument::Quoted("XPath expression")]),
+];
+
+/// Arguments of the filter called `name`, or `None` if there is no such filter.
+pub fn filter_arguments(name: &str) -> Option<&'static [FilterArgument]> {
+    FILTERS
+        .iter()
+        .find(|(filter, _)| *filter == name)
+        .map(|(_, arguments)| *arguments)
+}
+
+/// Value a predicate expects after its name.
+#[derive(Clone, Copy)]
+pub enum PredicateValue {
+    None,
+    /// Any literal value.
+    Any,
+    /// A number, or a quoted string compared lexicographically.
+    Comparable,
+    /// A string or bytes.
+    Text,
+    /// A quoted string or a `/regex/`.
+    Pattern,
+}
+
+impl PredicateValue {
+    pub fn description(self) -> &'static str {
+        match self {
+            PredicateValue::None => "no value",
+            PredicateValue::Any => "a quoted string, a number, a boolean, null or bytes",
+            PredicateValue::Comparable => "a number or a quoted string",
+            PredicateValue::Text => "a quoted string or bytes",
+            PredicateValue::Pattern => "a quoted string or a /regex/",
+        }
+    }
+
+    pub fn accepts(self, value: &Value) -> bool {
+        match (self, value) {
+            // A template may expand to anything.
+            (_, Value::Template(_)) => true,
+            (PredicateValue::None, _) => false,
+            (PredicateValue::Any, value) => !matches!(value, Value::Regex(_) | Value::Word(_)),
+            (PredicateValue::Comparable, value) => {
+                matches!(
+                    value,
+                    Value::Integer(_) | Value::Float(_) | Value::String(_)
+                )
+            }
+            (PredicateValue::Text, value) => matches!(
+                value,
+                Value::String(_) | Value::MultilineString(_) | Value::Bytes(_)
+            ),
+            (PredicateValue::Pattern, value) => {
+                matches!(value, Value::String(_) | Value::Regex(_))
+            }
+        }
+    }
+}
+
+pub const PREDICATES: &[(&str, PredicateValue)] = &[
+    ("==", PredicateValue::Any),
+    ("!=", PredicateValue::Any),
+    (">", PredicateValue::Comparable),
+    (">=", PredicateValue::Comparable),
+    ("<", PredicateValue::Comparable),
+    ("<=", PredicateValue::Comparable),
+    ("startsWith", PredicateValue::Text),
+    ("endsWith", PredicateValue::Text),
+    ("contains", PredicateValue::Text),
+    ("includes", PredicateValue::Any),
+    ("matches", PredicateValue::Pattern),
+    ("exists", PredicateValue::None),
+    ("isBoolean", PredicateValue::None),
+    ("isCollection", PredicateValue::None),
+    ("isDate", PredicateValue::None),
+    ("isEmpty", PredicateValue::None),
+    ("isFloat", PredicateValue::None),
+    ("isInteger", PredicateValue::None),
+    ("isIpv4", PredicateValue::None),
+    ("isIpv6", PredicateValue::None),
+    ("isIsoDate", PredicateValue::None),
+    ("isNumber", PredicateValue::None),
+    ("isString", PredicateValue::None),
+    ("isUuid", PredicateValue::None),
+];
@@ -0,0 +1,53 @@
+// Registry of the standard HTTP headers shipped in `static_data/headers.json`, used to complete
+// and document header names and values.
+
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+/// Messages a header is sent in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderUsage {
+    Request,
+    Response,
+    Both,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HeaderInfo {
+    pub name: String,
+    pub usage: HeaderUsage,
+    pub description: String,
+    /// Common values, offered when completing the value of the header.
+    pub values: Vec<String>,
+}
+
+impl HeaderInfo {
+    pub fn in_request(&self) -> bool {
+        self.usage != HeaderUsage::Response
+    }
+
+    pub fn in_response(&self) -> bool {
+        self.usage != HeaderUsage::Request
+    }
+
+    pub fn documentation(&self) -> String {
+        format!("### {}\n\n{}", self.name, self.description)
+    }
+}
+
+pub fn get_headers() -> &'static [HeaderInfo] {
+    static HEADERS: OnceLock<Vec<HeaderInfo>> = OnceLock::new();
+    HEADERS.get_or_init(|| {
+        serde_json::from_str(include_str!("static_data/headers.json"))
+            .expect("static_data/headers.json is not a valid header registry")
+    })
+}
+
+/// Header called `name`. Names are compared case-insensitively like HTTP does.
+pub fn find_header(name: &str) -> Option<&'static HeaderInfo> {
+    get_headers()
+        .iter()
+        .find(|header| header.name.eq_ignore_ascii_case(name))
+}
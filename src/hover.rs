@@ -0,0 +1,126 @@
+use crate::ast::{Filter, HurlFile, Pos, Query, SectionContent, SectionKind, Spanned};
+use crate::document::{Document, Positions};
+use crate::documentation::DOCUMENTATION_MAP;
+use crate::grammar::STATUS_CODES;
+use crate::headers;
+
+pub fn get_hover(document: &Document, position: lsp_types::Position) -> Option<lsp_types::Hover> {
+    let tree = &document.tree;
+    let positions = document.positions();
+    let pos = positions.pos(position);
+    if let Some(hover) = get_status_hover(tree, pos, positions) {
+        return Some(hover);
+    }
+    if let Some(hover) = get_header_hover(tree, pos, positions) {
+        return Some(hover);
+    }
+
+    // Names under the cursor along with the key of their documentation.
+    let (name, key) = tree
+        .entries
+        .iter()
+        .flat_map(|entry| entry.sections())
+        .flat_map(|section| -> Vec<(&Spanned<String>, String)> {
+            match &section.content {
+                SectionContent::Asserts(asserts) => asserts
+                    .iter()
+                    .flat_map(|assert| query_names(&assert.query, &assert.filters))
+                    .collect(),
+                SectionContent::Captures(captures) => captures
+                    .iter()
+                    .filter_map(|capture| {
+                        Some(query_names(capture.query.as_ref()?, &capture.filters))
+                    })
+                    .flatten()
+                    .collect(),
+                SectionContent::KeyValues(options) if section.kind == SectionKind::Options => {
+                    options
+                        .iter()
+                        .map(|option| (&option.key, format!("options/{}", option.key.value)))
+                        .collect()
+                }
+                SectionContent::KeyValues(_) => vec![],
+            }
+        })
+        .find(|(name, _)| name.span.contains(pos))?;
+
+    let value = DOCUMENTATION_MAP.get(key.as_str())?;
+    Some(lsp_types::Hover {
+        contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
+            kind: lsp_types::MarkupKind::Markdown,
+            value: value.to_string(),
+        }),
+        range: Some(positions.range(name.span)),
+    })
+}
+
+/// Names of a query and of its filters with the key of their documentation.
+fn query_names<'a>(
+    query: &'a Query,
+    filters: &'a [Filter],
+) -> impl Iterator<Item = (&'a Spanned<String>, String)> {
+    std::iter::once((&query.name, query.name.value.clone())).chain(
+        filters
+            .iter()
+            .map(|filter| (&filter.name, format!("filters/{}", filter.name.value))),
+    )
+}
+
+/// Standard meaning of the status code of a response line.
+fn get_status_hover(tree: &HurlFile, pos: Pos, positions: Positions) -> Option<lsp_types::Hover> {
+    let status = tree
+        .entries
+        .iter()
+        .filter_map(|entry| entry.response.as_ref())
+        .map(|response| &response.status)
+        .find(|status| !status.value.is_empty() && status.span.contains(pos))?;
+    let value = match STATUS_CODES
+        .iter()
+        .find(|(code, _, _)| *code == status.value)
+    {
+        Some((code, reason, description)) => format!("### {} {}\n\n{}", code, reason, description),
+        None => {
+            let class = match status.value.parse::<u16>().ok()? {
+                100..=199 => "Informational response",
+                200..=299 => "Successful response",
+                300..=399 => "Redirection",
+                400..=499 => "Client error",
+                500..=599 => "Server error",
+                _ => return None,
+            };
+            format!("### {}\n\n{}", status.value, class)
+        }
+    };
+    Some(lsp_types::Hover {
+        contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
+            kind: lsp_types::MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(positions.range(status.span)),
+    })
+}
+
+/// Description of a standard header of a request or a response.
+fn get_header_hover(tree: &HurlFile, pos: Pos, positions: Positions) -> Option<lsp_types::Hover> {
+    let key = tree
+        .entries
+        .iter()
+        .flat_map(|entry| {
+            entry.request.headers.iter().chain(
+                entry
+                    .response
+                    .iter()
+                    .flat_map(|response| response.headers.iter()),
+            )
+        })
+        .map(|header| &header.key)
+        .find(|key| key.span.contains(pos))?;
+    let header = headers::find_header(&key.value)?;
+    Some(lsp_types::Hover {
+        contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
+            kind: lsp_types::MarkupKind::Markdown,
+            value: header.documentation(),
+        }),
+        range: Some(positions.range(key.span)),
+    })
+}
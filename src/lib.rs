@@ -0,0 +1,16 @@
+pub mod ast;
+pub mod completion;
+pub mod definition;
+pub mod diagnostics;
+pub mod document;
+pub mod documentation;
+pub mod folding;
+pub mod grammar;
+pub mod headers;
+pub mod hover;
+pub mod outline;
+pub mod parser;
+pub mod references;
+pub mod semantic_tokens;
+pub mod symbols;
+pub mod workspace;
@@ -1,10 +1,14 @@
-use std::{collections::HashMap, error::Error, str::FromStr, sync::Mutex};
+use std::{collections::HashMap, error::Error, sync::Mutex};
 
 use futures::sink::SinkExt;
 use futures::StreamExt;
+use hurlsp::document::PositionEncoding;
+use hurlsp::symbols::{ExternalVariables, VariablesFile};
+use hurlsp::workspace::WorkspaceIndex;
+use hurlsp::{
+    completion, definition, document, folding, hover, outline, references, semantic_tokens,
+};
 use log::{debug, error, info, LevelFilter};
-use phf::phf_map;
-use ropey::Rope;
 use serde::Serialize;
 use std::io::Write;
 use tokio::io::AsyncWrite;
@@ -88,42 +92,18 @@ fn number_of_digits(mut n: usize) -> usize {
     num_digits
 }
 
-#[derive(Debug, Default, Clone)]
-struct HurlSectionPositions {
-    pub request: Option<usize>,
-    pub response: Option<usize>,
-    pub query: Option<usize>,
-    pub form: Option<usize>,
-    pub multipart: Option<usize>,
-    pub cookies: Option<usize>,
-    pub capture: Option<usize>,
-    pub asserts: Option<usize>,
-    pub basic_auth: Option<usize>,
-    pub options: Option<usize>,
-}
-impl FromStr for HurlSectionPositions {
-    type Err = Box<dyn Error>;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let asserts = memchr::memmem::find(s.as_bytes(), b"\n[Asserts]\n");
-        Ok(HurlSectionPositions {
-            // Adding 1 to the index because I don't care about the first \n. I care where
-            // [Asserts] starts
-            asserts: asserts.map(|index| index + 1),
-            ..Default::default()
-        })
-    }
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut files: Mutex<HashMap<String, Rope>> = Mutex::new(HashMap::new());
-    let mut positions: Mutex<HashMap<String, HurlSectionPositions>> = Mutex::new(HashMap::new());
+    let documents: Mutex<HashMap<String, document::Document>> = Mutex::new(HashMap::new());
+    let mut externals = ExternalVariables::default();
+    let mut index = WorkspaceIndex::default();
+    let mut encoding = PositionEncoding::Utf16;
+    let mut watch_files = false;
 
     simple_logging::log_to_file("pat.log", LevelFilter::Debug)?;
     info!("Starting!");
     let input = tokio::io::stdin();
-    let mut output = tokio::io::stdout();
+    let output = tokio::io::stdout();
     let mut framed_reader = tokio_util::codec::FramedRead::new(input, JsonRPCMessageCodec {});
     let mut framed_writer = tokio_util::codec::FramedWrite::new(output, JsonRPCMessageCodec {});
     //let a = framed_reader.next().await;
@@ -144,12 +124,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     info!("Recieved {}", method);
                     match method {
                         "initialize" => {
+                            if let Some(params) = msg.get("params") {
+                                match serde_json::from_value(params.clone()) {
+                                    Ok(parsed) => {
+                                        externals = load_external_variables(&parsed);
+                                        encoding = position_encoding(&parsed);
+                                        index = WorkspaceIndex::new(
+                                            &workspace_roots(&parsed),
+                                            encoding,
+                                        );
+                                        watch_files = can_watch_files(&parsed);
+                                    }
+                                    Err(_) => error!("Failed to parse initialize params"),
+                                }
+                            }
                             let result = lsp_types::InitializeResult {
                                 server_info: Some(lsp_types::ServerInfo {
                                     name: "hurlsp".to_string(),
                                     version: Some("0.0.1".to_string()),
                                 }),
                                 capabilities: lsp_types::ServerCapabilities {
+                                    position_encoding: Some(match encoding {
+                                        PositionEncoding::Utf16 => {
+                                            lsp_types::PositionEncodingKind::UTF16
+                                        }
+                                        PositionEncoding::Utf32 => {
+                                            lsp_types::PositionEncodingKind::UTF32
+                                        }
+                                    }),
                                     hover_provider: Some(
                                         lsp_types::HoverProviderCapability::Options(
                                             lsp_types::HoverOptions {
@@ -160,6 +162,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                             },
                                         ),
                                     ),
+                                    definition_provider: Some(lsp_types::OneOf::Left(true)),
+                                    references_provider: Some(lsp_types::OneOf::Left(true)),
+                                    document_symbol_provider: Some(lsp_types::OneOf::Left(true)),
+                                    workspace_symbol_provider: Some(lsp_types::OneOf::Left(true)),
+                                    semantic_tokens_provider: Some(
+                                        lsp_types::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                                            lsp_types::SemanticTokensOptions {
+                                                legend: semantic_tokens::legend(),
+                                                range: Some(true),
+                                                full: Some(lsp_types::SemanticTokensFullOptions::Bool(true)),
+                                                ..lsp_types::SemanticTokensOptions::default()
+                                            },
+                                        ),
+                                    ),
+                                    folding_range_provider: Some(
+                                        lsp_types::FoldingRangeProviderCapability::Simple(true),
+                                    ),
+                                    rename_provider: Some(lsp_types::OneOf::Right(
+                                        lsp_types::RenameOptions {
+                                            prepare_provider: Some(true),
+                                            work_done_progress_options:
+                                                lsp_types::WorkDoneProgressOptions::default(),
+                                        },
+                                    )),
                                     completion_provider: Some(lsp_types::CompletionOptions {
                                         resolve_provider: Some(true),
                                         ..lsp_types::CompletionOptions::default()
@@ -168,19 +194,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                         lsp_types::TextDocumentSyncCapability::Options(
                                             lsp_types::TextDocumentSyncOptions {
                                                 open_close: Some(true),
-                                                change: Some(lsp_types::TextDocumentSyncKind::FULL),
+                                                change: Some(
+                                                    lsp_types::TextDocumentSyncKind::INCREMENTAL,
+                                                ),
+                                                save: Some(
+                                                    lsp_types::TextDocumentSyncSaveOptions::Supported(
+                                                        true,
+                                                    ),
+                                                ),
                                                 ..lsp_types::TextDocumentSyncOptions::default()
                                             },
                                         ),
                                     ),
                                     ..lsp_types::ServerCapabilities::default()
                                 },
-                                ..Default::default()
                             };
 
                             let _ = write_result(msg, result, &mut framed_writer).await;
                         }
-                        "initialized" => {}
+                        "initialized" => {
+                            if watch_files {
+                                let _ = write_request(
+                                    REGISTER_CAPABILITY_ID,
+                                    "client/registerCapability".to_string(),
+                                    watch_hurl_files(),
+                                    &mut framed_writer,
+                                )
+                                .await;
+                            }
+                        }
                         "textDocument/didChange" => {
                             if let Some(params) = msg.get("params") {
                                 let parsed: Result<lsp_types::DidChangeTextDocumentParams, _> =
@@ -191,103 +233,47 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                     }
                                     Ok(parsed) => {
                                         let uri = parsed.text_document.uri.to_string();
-                                        let content = parsed.content_changes.last();
-                                        if content.is_none() {
-                                            continue;
-                                        }
-                                        let text = &content.unwrap().text;
-                                        let content = Rope::from_str(text.as_str());
-                                        let content_positions =
-                                            HurlSectionPositions::from_str(text.as_str()).unwrap();
-                                        match files.lock() {
-                                            Ok(mut f) => {
-                                                f.insert(uri.to_owned(), content.to_owned());
-                                            }
-                                            Err(e) => error!("Failed to lock file mutex: {}", e),
-                                        }
-                                        match positions.lock() {
-                                            Ok(mut p) => {
-                                                p.insert(
-                                                    uri.to_owned(),
-                                                    content_positions.to_owned(),
-                                                );
-                                            }
-                                            Err(e) => error!("Failed to lock file mutex: {}", e),
-                                        }
-                                        if content_positions.asserts.is_none() {
-                                            // If there's no assert section, there's no diagnostics
-                                            // for now.
-                                            continue;
-                                        }
-                                        let mut diagnotics: Vec<lsp_types::Diagnostic> = vec![];
-                                        let make_diagnostic =
-                                            |range: lsp_types::Range| -> lsp_types::Diagnostic {
-                                                lsp_types::Diagnostic {
-                                                    range,
-                                                    severity: Some(
-                                                        lsp_types::DiagnosticSeverity::ERROR,
-                                                    ),
-                                                    source: Some("LSP".to_string()),
-                                                    message: "Invalid assert".to_string(),
-                                                    ..Default::default()
+                                        let diagnostics = match documents.lock() {
+                                            Ok(mut d) => match d.get_mut(&uri) {
+                                                Some(document) => {
+                                                    match document.apply_changes(
+                                                        parsed.text_document.version,
+                                                        &parsed.content_changes,
+                                                    ) {
+                                                        Ok(()) => {
+                                                            index.update(
+                                                                parsed.text_document.uri.clone(),
+                                                                document,
+                                                            );
+                                                            document.diagnostics(&externals)
+                                                        }
+                                                        Err(e) => {
+                                                            error!(
+                                                                "Skipping change to {}: {}",
+                                                                uri, e
+                                                            );
+                                                            continue;
+                                                        }
+                                                    }
                                                 }
-                                            };
-
-                                        let assert_block_line = &content
-                                            .char_to_line(content_positions.asserts.unwrap());
-                                        for (offset, line) in
-                                            content.lines_at(*assert_block_line).skip(1).enumerate()
-                                        {
-                                            let mut first_token = "".to_string();
-                                            let mut done = false;
-                                            let mut chars = line.chars();
-                                            while !done {
-                                                let c = chars.next();
-                                                if c.is_none() {
-                                                    done = true;
-                                                    continue;
-                                                }
-                                                let c = c.unwrap();
-                                                if ['\n', ' ', '#'].contains(&c) {
-                                                    done = true;
+                                                None => {
+                                                    error!(
+                                                        "Received change for unknown document {}",
+                                                        uri
+                                                    );
                                                     continue;
                                                 }
-                                                first_token = format!("{}{}", first_token, c);
-                                            }
-                                            if !["", "jsonpath"].contains(&first_token.as_str()) {
-                                                let diagnostic_line =
-                                                    *assert_block_line + 1 + offset;
-                                                info!("{diagnostic_line}");
-                                                let end_character = if first_token.len() == 0 {
-                                                    0
-                                                } else {
-                                                    first_token.len() - 1
-                                                };
-                                                diagnotics.push(make_diagnostic(
-                                                    lsp_types::Range {
-                                                        start: lsp_types::Position {
-                                                            line: diagnostic_line
-                                                                .try_into()
-                                                                .unwrap(),
-                                                            character: 0,
-                                                        },
-                                                        end: lsp_types::Position {
-                                                            line: diagnostic_line
-                                                                .try_into()
-                                                                .unwrap(),
-                                                            character: end_character
-                                                                .try_into()
-                                                                .unwrap(),
-                                                        },
-                                                    },
-                                                ));
+                                            },
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e);
+                                                continue;
                                             }
-                                        }
+                                        };
 
                                         let diagnotics_result =
                                             lsp_types::PublishDiagnosticsParams {
-                                                uri: url::Url::parse(&uri).unwrap(),
-                                                diagnostics: diagnotics,
+                                                uri: parsed.text_document.uri,
+                                                diagnostics,
                                                 version: Some(parsed.text_document.version),
                                             };
                                         let _ = write_notification(
@@ -310,26 +296,129 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                     }
                                     Ok(parsed) => {
                                         let uri = parsed.text_document.uri.to_string();
-                                        let text = parsed.text_document.text;
-                                        let content_positions =
-                                            HurlSectionPositions::from_str(text.as_str()).unwrap();
-                                        let content = Rope::from_str(text.as_str());
-                                        match files.lock() {
-                                            Ok(mut f) => {
-                                                f.insert(uri.to_owned(), content);
+                                        let document = document::Document::new(
+                                            parsed.text_document.text.as_str(),
+                                            parsed.text_document.version,
+                                            encoding,
+                                        );
+                                        index.update(parsed.text_document.uri.clone(), &document);
+                                        let diagnostics = document.diagnostics(&externals);
+                                        match documents.lock() {
+                                            Ok(mut d) => {
+                                                d.insert(uri, document);
+                                            }
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e)
                                             }
-                                            Err(e) => error!("Failed to lock file mutex: {}", e),
                                         }
-                                        match positions.lock() {
-                                            Ok(mut p) => {
-                                                p.insert(uri, content_positions);
+                                        let diagnostics_result =
+                                            lsp_types::PublishDiagnosticsParams {
+                                                uri: parsed.text_document.uri,
+                                                diagnostics,
+                                                version: Some(parsed.text_document.version),
+                                            };
+                                        let _ = write_notification(
+                                            "textDocument/publishDiagnostics".to_string(),
+                                            diagnostics_result,
+                                            &mut framed_writer,
+                                        )
+                                        .await;
+                                    }
+                                };
+                            };
+                        }
+                        "textDocument/didClose" => {
+                            if let Some(params) = msg.get("params") {
+                                let parsed: Result<lsp_types::DidCloseTextDocumentParams, _> =
+                                    serde_json::from_value(params.clone());
+                                match parsed {
+                                    Err(_) => {
+                                        error!("Failed to parse textDocument/didClose params")
+                                    }
+                                    Ok(parsed) => {
+                                        let uri = parsed.text_document.uri;
+                                        match documents.lock() {
+                                            Ok(mut d) => {
+                                                d.remove(&uri.to_string());
+                                            }
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e)
                                             }
-                                            Err(e) => error!("Failed to lock file mutex: {}", e),
+                                        }
+                                        // The edits may not have been saved, so index what is
+                                        // on disk again.
+                                        match uri.to_file_path() {
+                                            Ok(path) if path.is_file() => index.load(&path),
+                                            _ => index.remove(&uri),
                                         }
                                     }
                                 };
                             };
                         }
+                        "textDocument/didSave" => {
+                            if let Some(params) = msg.get("params") {
+                                let parsed: Result<lsp_types::DidSaveTextDocumentParams, _> =
+                                    serde_json::from_value(params.clone());
+                                match parsed {
+                                    Err(_) => {
+                                        error!("Failed to parse textDocument/didSave params")
+                                    }
+                                    Ok(parsed) => {
+                                        let uri = parsed.text_document.uri;
+                                        match documents.lock() {
+                                            Ok(d) => match d.get(&uri.to_string()) {
+                                                Some(document) => index.update(uri, document),
+                                                None => {
+                                                    if let Ok(path) = uri.to_file_path() {
+                                                        index.load(&path);
+                                                    }
+                                                }
+                                            },
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e)
+                                            }
+                                        }
+                                    }
+                                };
+                            };
+                        }
+                        "workspace/didChangeWatchedFiles" => {
+                            if let Some(params) = msg.get("params") {
+                                let parsed: Result<lsp_types::DidChangeWatchedFilesParams, _> =
+                                    serde_json::from_value(params.clone());
+                                match parsed {
+                                    Err(_) => {
+                                        error!(
+                                            "Failed to parse workspace/didChangeWatchedFiles params"
+                                        )
+                                    }
+                                    Ok(parsed) => match documents.lock() {
+                                        Ok(d) => {
+                                            for change in parsed.changes {
+                                                // Open documents are indexed as they are edited.
+                                                if d.contains_key(&change.uri.to_string()) {
+                                                    continue;
+                                                }
+                                                match change.typ {
+                                                    lsp_types::FileChangeType::DELETED => {
+                                                        index.remove(&change.uri)
+                                                    }
+                                                    _ => {
+                                                        if let Ok(path) = change.uri.to_file_path()
+                                                        {
+                                                            index.load(&path);
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        Err(e) => {
+                                            error!("Failed to lock document mutex: {}", e)
+                                        }
+                                    },
+                                };
+                            };
+                        }
                         "textDocument/completion" => {
                             if let Some(params) = msg.get("params") {
                                 let parsed: Result<lsp_types::CompletionParams, _> =
@@ -344,73 +433,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                             .text_document
                                             .uri
                                             .to_string();
-
-                                        let position_guard = positions.lock().unwrap();
-                                        let p = position_guard.get(&uri);
-                                        if p.is_none() {
-                                            let result =
-                                                lsp_types::CompletionResponse::Array(vec![]);
-                                            let _ =
-                                                write_result(msg, result, &mut framed_writer).await;
-                                            continue;
-                                        }
-                                        let p = p.unwrap();
-                                        if p.asserts.is_none() {
-                                            let result =
-                                                lsp_types::CompletionResponse::Array(vec![]);
-                                            let _ =
-                                                write_result(msg, result, &mut framed_writer).await;
-                                            continue;
-                                        }
-
-                                        let guard = files.lock().unwrap();
-                                        let content = guard.get(&uri);
-                                        match content {
-                                            None => {
-                                                let result =
-                                                    lsp_types::CompletionResponse::Array(vec![]);
-                                                let _ =
-                                                    write_result(msg, result, &mut framed_writer)
-                                                        .await;
-                                                continue;
-                                            }
-                                            Some(content) => {
-                                                let (text, _, _, _) = content.chunk_at_char(0);
-                                                if ropey::str_utils::char_to_line_idx(
-                                                    text,
-                                                    p.asserts.unwrap(),
-                                                ) < parsed
-                                                    .text_document_position
-                                                    .position
-                                                    .line
-                                                    .try_into()
-                                                    .unwrap()
-                                                {
-                                                    let items = get_static_completions();
-                                                    let result =
-                                                        lsp_types::CompletionResponse::Array(items);
-                                                    let _ = write_result(
-                                                        msg,
-                                                        result,
-                                                        &mut framed_writer,
-                                                    )
-                                                    .await;
-                                                    continue;
-                                                } else {
-                                                    let result =
-                                                        lsp_types::CompletionResponse::Array(
-                                                            vec![],
-                                                        );
-                                                    let _ = write_result(
-                                                        msg,
-                                                        result,
-                                                        &mut framed_writer,
-                                                    )
-                                                    .await;
-                                                    continue;
-                                                }
+                                        let items = match documents.lock() {
+                                            Ok(d) => match d.get(&uri) {
+                                                Some(document) => completion::get_completions(
+                                                    document,
+                                                    parsed.text_document_position.position,
+                                                    &externals,
+                                                ),
+                                                None => vec![],
+                                            },
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e);
+                                                vec![]
                                             }
                                         };
+                                        let result = lsp_types::CompletionResponse::Array(items);
+                                        let _ = write_result(msg, result, &mut framed_writer).await;
                                     }
                                 }
                             }
@@ -443,62 +481,274 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                             .text_document
                                             .uri
                                             .to_string();
-                                        let character_position =
-                                            parsed.text_document_position_params.position.character;
-                                        let line_position =
-                                            parsed.text_document_position_params.position.line;
-                                        let files_guard = files.lock().unwrap();
-                                        let content = files_guard.get(&uri).unwrap();
-                                        let line = content.line(line_position.try_into().unwrap());
-                                        let mut start_index: usize =
-                                            character_position.try_into().unwrap();
-                                        let mut end_index: usize =
-                                            character_position.try_into().unwrap();
-                                        info!("{}", character_position);
-
-                                        // Get start index of what we're hovering
-                                        while start_index >= 1 && line.char(start_index - 1) != ' '
-                                        {
-                                            start_index = start_index - 1;
-                                        }
-
-                                        // Get end index of what we're hovering
-                                        while line.char(end_index) != ' '
-                                            && line.char(end_index) != '\n'
-                                        {
-                                            end_index = end_index + 1;
-                                        }
-
-                                        let slice = line.slice(start_index..end_index);
-                                        let word = slice.to_string();
-                                        let value = match DOCUMENTATION_MAP.get(word.as_str()) {
-                                            None => "".to_string(),
-                                            Some(value) => value.to_string(),
+                                        let result = match documents.lock() {
+                                            Ok(d) => d.get(&uri).and_then(|document| {
+                                                hover::get_hover(
+                                                    document,
+                                                    parsed.text_document_position_params.position,
+                                                )
+                                            }),
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e);
+                                                None
+                                            }
                                         };
-                                        let result = lsp_types::Hover {
-                                            contents: lsp_types::HoverContents::Markup(
-                                                lsp_types::MarkupContent {
-                                                    kind: lsp_types::MarkupKind::Markdown,
-                                                    value,
-                                                },
+                                        let _ = write_result(msg, result, &mut framed_writer).await;
+                                    }
+                                }
+                            }
+                        }
+                        "textDocument/definition" => {
+                            if let Some(params) = msg.get("params") {
+                                let parsed: Result<lsp_types::GotoDefinitionParams, _> =
+                                    serde_json::from_value(params.clone());
+                                match parsed {
+                                    Err(_) => {
+                                        error!("Failed to parse textDocument/definition params")
+                                    }
+                                    Ok(parsed) => {
+                                        let position = parsed.text_document_position_params;
+                                        let uri = position.text_document.uri;
+                                        let result = match documents.lock() {
+                                            Ok(d) => d.get(&uri.to_string()).and_then(|document| {
+                                                definition::get_definition(
+                                                    document,
+                                                    &uri,
+                                                    position.position,
+                                                    &externals,
+                                                )
+                                            }),
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e);
+                                                None
+                                            }
+                                        };
+                                        let result =
+                                            result.map(lsp_types::GotoDefinitionResponse::Scalar);
+                                        let _ = write_result(msg, result, &mut framed_writer).await;
+                                    }
+                                }
+                            }
+                        }
+                        "textDocument/references" => {
+                            if let Some(params) = msg.get("params") {
+                                let parsed: Result<lsp_types::ReferenceParams, _> =
+                                    serde_json::from_value(params.clone());
+                                match parsed {
+                                    Err(_) => {
+                                        error!("Failed to parse textDocument/references params")
+                                    }
+                                    Ok(parsed) => {
+                                        let position = parsed.text_document_position;
+                                        let result = match documents.lock() {
+                                            Ok(d) => references::get_references(
+                                                &d,
+                                                &position.text_document.uri,
+                                                position.position,
+                                                parsed.context.include_declaration,
+                                                &externals,
+                                                &index,
                                             ),
-                                            range: Some(lsp_types::Range {
-                                                start: lsp_types::Position {
-                                                    line: line_position,
-                                                    character: start_index.try_into().unwrap(),
-                                                },
-                                                end: lsp_types::Position {
-                                                    line: line_position,
-                                                    character: end_index.try_into().unwrap(),
-                                                },
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e);
+                                                vec![]
+                                            }
+                                        };
+                                        let _ = write_result(msg, result, &mut framed_writer).await;
+                                    }
+                                }
+                            }
+                        }
+                        "textDocument/prepareRename" => {
+                            if let Some(params) = msg.get("params") {
+                                let parsed: Result<lsp_types::TextDocumentPositionParams, _> =
+                                    serde_json::from_value(params.clone());
+                                match parsed {
+                                    Err(_) => {
+                                        error!("Failed to parse textDocument/prepareRename params")
+                                    }
+                                    Ok(parsed) => {
+                                        let uri = parsed.text_document.uri.to_string();
+                                        let result = match documents.lock() {
+                                            Ok(d) => d.get(&uri).and_then(|document| {
+                                                references::prepare_rename(
+                                                    document,
+                                                    parsed.position,
+                                                )
                                             }),
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e);
+                                                None
+                                            }
                                         };
                                         let _ = write_result(msg, result, &mut framed_writer).await;
                                     }
                                 }
                             }
                         }
-
+                        "textDocument/rename" => {
+                            if let Some(params) = msg.get("params") {
+                                let parsed: Result<lsp_types::RenameParams, _> =
+                                    serde_json::from_value(params.clone());
+                                match parsed {
+                                    Err(_) => {
+                                        error!("Failed to parse textDocument/rename params")
+                                    }
+                                    Ok(parsed) => {
+                                        let position = parsed.text_document_position;
+                                        let result = match documents.lock() {
+                                            Ok(d) => references::rename(
+                                                &d,
+                                                &position.text_document.uri,
+                                                position.position,
+                                                &parsed.new_name,
+                                                &externals,
+                                                &index,
+                                            ),
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e);
+                                                Ok(None)
+                                            }
+                                        };
+                                        let _ = match result {
+                                            Ok(result) => {
+                                                write_result(msg, result, &mut framed_writer).await
+                                            }
+                                            Err(e) => {
+                                                write_error(
+                                                    msg,
+                                                    INVALID_PARAMS,
+                                                    e,
+                                                    &mut framed_writer,
+                                                )
+                                                .await
+                                            }
+                                        };
+                                    }
+                                }
+                            }
+                        }
+                        "textDocument/documentSymbol" => {
+                            if let Some(params) = msg.get("params") {
+                                let parsed: Result<lsp_types::DocumentSymbolParams, _> =
+                                    serde_json::from_value(params.clone());
+                                match parsed {
+                                    Err(_) => {
+                                        error!("Failed to parse textDocument/documentSymbol params")
+                                    }
+                                    Ok(parsed) => {
+                                        let uri = parsed.text_document.uri.to_string();
+                                        let result = match documents.lock() {
+                                            Ok(d) => d.get(&uri).map(|document| {
+                                                lsp_types::DocumentSymbolResponse::Nested(
+                                                    outline::get_document_symbols(
+                                                        &document.tree,
+                                                        document.positions(),
+                                                    ),
+                                                )
+                                            }),
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e);
+                                                None
+                                            }
+                                        };
+                                        let _ = write_result(msg, result, &mut framed_writer).await;
+                                    }
+                                }
+                            }
+                        }
+                        "textDocument/foldingRange" => {
+                            if let Some(params) = msg.get("params") {
+                                let parsed: Result<lsp_types::FoldingRangeParams, _> =
+                                    serde_json::from_value(params.clone());
+                                match parsed {
+                                    Err(_) => {
+                                        error!("Failed to parse textDocument/foldingRange params")
+                                    }
+                                    Ok(parsed) => {
+                                        let uri = parsed.text_document.uri.to_string();
+                                        let result = match documents.lock() {
+                                            Ok(d) => d.get(&uri).map(|document| {
+                                                folding::get_folding_ranges(&document.tree)
+                                            }),
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e);
+                                                None
+                                            }
+                                        };
+                                        let _ = write_result(msg, result, &mut framed_writer).await;
+                                    }
+                                }
+                            }
+                        }
+                        "textDocument/semanticTokens/full" => {
+                            if let Some(params) = msg.get("params") {
+                                let parsed: Result<lsp_types::SemanticTokensParams, _> =
+                                    serde_json::from_value(params.clone());
+                                match parsed {
+                                    Err(_) => {
+                                        error!("Failed to parse textDocument/semanticTokens/full params")
+                                    }
+                                    Ok(parsed) => {
+                                        let uri = parsed.text_document.uri.to_string();
+                                        let result = match documents.lock() {
+                                            Ok(d) => d.get(&uri).map(|document| {
+                                                semantic_tokens::get_semantic_tokens(document, None)
+                                            }),
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e);
+                                                None
+                                            }
+                                        };
+                                        let _ = write_result(msg, result, &mut framed_writer).await;
+                                    }
+                                }
+                            }
+                        }
+                        "textDocument/semanticTokens/range" => {
+                            if let Some(params) = msg.get("params") {
+                                let parsed: Result<lsp_types::SemanticTokensRangeParams, _> =
+                                    serde_json::from_value(params.clone());
+                                match parsed {
+                                    Err(_) => {
+                                        error!("Failed to parse textDocument/semanticTokens/range params")
+                                    }
+                                    Ok(parsed) => {
+                                        let uri = parsed.text_document.uri.to_string();
+                                        let result = match documents.lock() {
+                                            Ok(d) => d.get(&uri).map(|document| {
+                                                semantic_tokens::get_semantic_tokens(
+                                                    document,
+                                                    Some(parsed.range),
+                                                )
+                                            }),
+                                            Err(e) => {
+                                                error!("Failed to lock document mutex: {}", e);
+                                                None
+                                            }
+                                        };
+                                        let _ = write_result(msg, result, &mut framed_writer).await;
+                                    }
+                                }
+                            }
+                        }
+                        "workspace/symbol" => {
+                            if let Some(params) = msg.get("params") {
+                                let parsed: Result<lsp_types::WorkspaceSymbolParams, _> =
+                                    serde_json::from_value(params.clone());
+                                match parsed {
+                                    Err(_) => {
+                                        error!("Failed to parse workspace/symbol params")
+                                    }
+                                    Ok(parsed) => {
+                                        let result = lsp_types::WorkspaceSymbolResponse::Flat(
+                                            index.search(&parsed.query),
+                                        );
+                                        let _ = write_result(msg, result, &mut framed_writer).await;
+                                    }
+                                }
+                            }
+                        }
                         _ => error!("Unimplemented method: {}", method),
                     }
                 }
@@ -511,6 +761,117 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Counts characters in chars when the client supports it, which is how the tree counts them,
+/// and in UTF-16 code units otherwise as the protocol requires.
+fn position_encoding(params: &lsp_types::InitializeParams) -> PositionEncoding {
+    let utf32 = params
+        .capabilities
+        .general
+        .as_ref()
+        .and_then(|general| general.position_encodings.as_ref())
+        .is_some_and(|encodings| encodings.contains(&lsp_types::PositionEncodingKind::UTF32));
+    if utf32 {
+        PositionEncoding::Utf32
+    } else {
+        PositionEncoding::Utf16
+    }
+}
+
+/// Whether the client lets the server register file watchers, to learn about the Hurl files
+/// changed outside of the editor.
+fn can_watch_files(params: &lsp_types::InitializeParams) -> bool {
+    params
+        .capabilities
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.did_change_watched_files.as_ref())
+        .and_then(|watched_files| watched_files.dynamic_registration)
+        .unwrap_or(false)
+}
+
+/// Registration of a watcher for the Hurl files of the workspace, which keeps the index in sync
+/// with files changed on disk.
+fn watch_hurl_files() -> lsp_types::RegistrationParams {
+    let options = lsp_types::DidChangeWatchedFilesRegistrationOptions {
+        watchers: vec![lsp_types::FileSystemWatcher {
+            glob_pattern: lsp_types::GlobPattern::String("**/*.hurl".to_string()),
+            kind: None,
+        }],
+    };
+    lsp_types::RegistrationParams {
+        registrations: vec![lsp_types::Registration {
+            id: "watch-hurl-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(options).ok(),
+        }],
+    }
+}
+
+/// Folders of the workspace, falling back on the root given by older clients.
+fn workspace_roots(params: &lsp_types::InitializeParams) -> Vec<std::path::PathBuf> {
+    #[allow(deprecated)]
+    let uris: Vec<&lsp_types::Url> = match &params.workspace_folders {
+        Some(folders) => folders.iter().map(|folder| &folder.uri).collect(),
+        None => params.root_uri.iter().collect(),
+    };
+    uris.into_iter()
+        .filter_map(|uri| uri.to_file_path().ok())
+        .collect()
+}
+
+/// Variables of the `variables` initialization option, a map of names to values, and of the
+/// variables file.
+fn load_external_variables(params: &lsp_types::InitializeParams) -> ExternalVariables {
+    let options = params
+        .initialization_options
+        .as_ref()
+        .and_then(|options| options.get("variables"))
+        .and_then(|variables| variables.as_object())
+        .map(|variables| {
+            variables
+                .iter()
+                .map(|(name, value)| {
+                    let value = match value.as_str() {
+                        Some(value) => value.to_string(),
+                        None => value.to_string(),
+                    };
+                    (name.clone(), value)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    ExternalVariables {
+        file: load_variables_file(params),
+        options,
+    }
+}
+
+/// Loads the file given in the `variablesFile` initialization option, relative to the root of
+/// the workspace unless absolute.
+fn load_variables_file(params: &lsp_types::InitializeParams) -> Option<VariablesFile> {
+    let path = params
+        .initialization_options
+        .as_ref()?
+        .get("variablesFile")?
+        .as_str()?;
+    let mut path = std::path::PathBuf::from(path);
+    if path.is_relative() {
+        if let Some(root) = workspace_roots(params).first() {
+            path = root.join(path);
+        }
+    }
+    match VariablesFile::load(&path) {
+        Ok(file) => {
+            info!("Loaded variables file {}", path.display());
+            Some(file)
+        }
+        Err(e) => {
+            error!("Failed to read variables file {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
 async fn write_result<T, W>(
     msg: lsp_types::LSPAny,
     result: T,
@@ -521,16 +882,55 @@ where
     W: AsyncWrite,
     T: Serialize,
 {
+    let response = models::ResponseMessage::new(request_id(&msg), result);
+    writer.send(response).await
+}
+
+/// JSON-RPC error code for a request with invalid parameters.
+const INVALID_PARAMS: i64 = -32602;
+
+async fn write_error<W>(
+    msg: lsp_types::LSPAny,
+    code: i64,
+    message: String,
+    writer: &mut tokio_util::codec::FramedWrite<W, JsonRPCMessageCodec>,
+) -> Result<(), Box<dyn Error>>
+where
+    W: std::marker::Unpin,
+    W: AsyncWrite,
+{
+    let response = models::ErrorResponseMessage::new(request_id(&msg), code, message);
+    writer.send(response).await
+}
+
+fn request_id(msg: &lsp_types::LSPAny) -> i64 {
     let id = msg.get("id").unwrap();
-    let id = match id.as_i64() {
+    match id.as_i64() {
         Some(i) => i,
         None => match id.as_str() {
             Some(i) => i.parse::<i64>().unwrap_or(0),
             None => 0,
         },
-    };
-    let response = models::ResponseMessage::new(id, result);
-    writer.send(response).await
+    }
+}
+
+/// Id of the file watcher registration, the only request the server sends. Its response has
+/// nothing to act on and is skipped like any message without a method.
+const REGISTER_CAPABILITY_ID: i64 = 1;
+
+async fn write_request<T, W>(
+    id: i64,
+    method: String,
+    params: T,
+    writer: &mut tokio_util::codec::FramedWrite<W, JsonRPCMessageCodec>,
+) -> Result<(), Box<dyn Error>>
+where
+    W: std::marker::Unpin,
+    W: AsyncWrite,
+    T: Serialize,
+{
+    let request = models::Request::new(id, method, params);
+    writer.send(request).await
 }
 
 async fn write_notification<T, W>(
@@ -546,95 +946,3 @@ where
     let notification = models::Notification::new(method, params);
     writer.send(notification).await
 }
-
-fn get_static_completions() -> Vec<lsp_types::CompletionItem> {
-    vec![
-        lsp_types::CompletionItem {
-            label: "jsonpath".to_string(),
-            kind: Some(lsp_types::CompletionItemKind::TEXT),
-            documentation: Some(lsp_types::Documentation::MarkupContent(
-                lsp_types::MarkupContent {
-                    kind: lsp_types::MarkupKind::Markdown,
-                    value: include_str!("static_documentation/jsonpath.md").to_string(),
-                },
-            )),
-            ..Default::default()
-        },
-        lsp_types::CompletionItem {
-            label: "status".to_string(),
-            kind: Some(lsp_types::CompletionItemKind::TEXT),
-            ..Default::default()
-        },
-        lsp_types::CompletionItem {
-            label: "url".to_string(),
-            kind: Some(lsp_types::CompletionItemKind::TEXT),
-            ..Default::default()
-        },
-        lsp_types::CompletionItem {
-            label: "header".to_string(),
-            kind: Some(lsp_types::CompletionItemKind::TEXT),
-            ..Default::default()
-        },
-        lsp_types::CompletionItem {
-            label: "cookie".to_string(),
-            kind: Some(lsp_types::CompletionItemKind::TEXT),
-            ..Default::default()
-        },
-        lsp_types::CompletionItem {
-            label: "body".to_string(),
-            kind: Some(lsp_types::CompletionItemKind::TEXT),
-            ..Default::default()
-        },
-        lsp_types::CompletionItem {
-            label: "xpath".to_string(),
-            kind: Some(lsp_types::CompletionItemKind::TEXT),
-            ..Default::default()
-        },
-        lsp_types::CompletionItem {
-            label: "regex".to_string(),
-            kind: Some(lsp_types::CompletionItemKind::TEXT),
-            ..Default::default()
-        },
-        lsp_types::CompletionItem {
-            label: "variable".to_string(),
-            kind: Some(lsp_types::CompletionItemKind::TEXT),
-            ..Default::default()
-        },
-        lsp_types::CompletionItem {
-            label: "duration".to_string(),
-            kind: Some(lsp_types::CompletionItemKind::TEXT),
-            ..Default::default()
-        },
-        lsp_types::CompletionItem {
-            label: "sha256".to_string(),
-            kind: Some(lsp_types::CompletionItemKind::TEXT),
-            ..Default::default()
-        },
-        lsp_types::CompletionItem {
-            label: "md5".to_string(),
-            kind: Some(lsp_types::CompletionItemKind::TEXT),
-            ..Default::default()
-        },
-        lsp_types::CompletionItem {
-            label: "bytes".to_string(),
-            kind: Some(lsp_types::CompletionItemKind::TEXT),
-            ..Default::default()
-        },
-    ]
-}
-
-const DOCUMENTATION_MAP: phf::Map<&'static str, &'static str> = phf_map! {
-    "jsonpath" => include_str!("static_documentation/jsonpath.md"),
-    "status" => include_str!("static_documentation/status.md"),
-    "url" => include_str!("static_documentation/url.md"),
-    "header" => include_str!("static_documentation/header.md"),
-    "cookie" => include_str!("static_documentation/cookie.md"),
-    "body" => include_str!("static_documentation/body.md"),
-    "xpath" => include_str!("static_documentation/xpath.md"),
-    "regex" => include_str!("static_documentation/regex.md"),
-    "variable" => include_str!("static_documentation/variable.md"),
-    "duration" => include_str!("static_documentation/duration.md"),
-    "sha256" => include_str!("static_documentation/sha256.md"),
-    "md5" => include_str!("static_documentation/md5.md"),
-    "bytes" => include_str!("static_documentation/bytes.md"),
-};
@@ -15,6 +15,7 @@ pub enum Id {
 }
 
 #[derive(Deserialize, Serialize)]
+#[allow(dead_code)]
 pub struct RequestMessage {
     #[serde(flatten)]
     pub message: Message,
@@ -23,6 +24,30 @@ pub struct RequestMessage {
     pub method: String,
 }
 
+/// A request sent by the server to the client.
+#[derive(Deserialize, Serialize)]
+pub struct Request<T> {
+    #[serde(flatten)]
+    pub message: Message,
+
+    pub id: Id,
+    pub method: String,
+    pub params: T,
+}
+
+impl<T> Request<T> {
+    pub fn new(id: i64, method: String, params: T) -> Self {
+        Self {
+            message: Message {
+                jsonrpc: "2.0".to_string(),
+            },
+            id: Id::Number(id),
+            method,
+            params,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct ResponseMessage<T> {
     #[serde(flatten)]
@@ -44,6 +69,33 @@ impl<T> ResponseMessage<T> {
     }
 }
 
+#[derive(Deserialize, Serialize)]
+pub struct ResponseError {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ErrorResponseMessage {
+    #[serde(flatten)]
+    pub message: Message,
+
+    pub id: Id,
+    pub error: ResponseError,
+}
+
+impl ErrorResponseMessage {
+    pub fn new(id: i64, code: i64, message: String) -> Self {
+        Self {
+            message: Message {
+                jsonrpc: "2.0".to_string(),
+            },
+            id: Id::Number(id),
+            error: ResponseError { code, message },
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct Notification<T> {
     #[serde(flatten)]
@@ -0,0 +1,103 @@
+use crate::ast::{Entry, HurlFile, Section, SectionContent, Span};
+use crate::document::Positions;
+
+/// Outline of the document: an entry per request, named after its method and URL, holding its
+/// sections, with the variables of the `[Captures]` sections below them.
+pub fn get_document_symbols(
+    tree: &HurlFile,
+    positions: Positions,
+) -> Vec<lsp_types::DocumentSymbol> {
+    tree.entries
+        .iter()
+        .map(|entry| entry_symbol(entry, positions))
+        .collect()
+}
+
+/// Name of an entry in outlines: its method followed by its URL.
+pub fn entry_name(entry: &Entry) -> String {
+    let request = &entry.request;
+    format!("{} {}", request.method.value, request.url.value)
+        .trim_end()
+        .to_string()
+}
+
+fn entry_symbol(entry: &Entry, positions: Positions) -> lsp_types::DocumentSymbol {
+    let request = &entry.request;
+    let name = entry_name(entry);
+    let selection = Span::new(request.method.span.start, request.url.span.end);
+    let children = entry
+        .sections()
+        .map(|section| section_symbol(section, positions))
+        .collect();
+    make_symbol(
+        name,
+        lsp_types::SymbolKind::FUNCTION,
+        positions.range(entry.span),
+        positions.range(selection),
+        Some(children),
+    )
+}
+
+fn section_symbol(section: &Section, positions: Positions) -> lsp_types::DocumentSymbol {
+    let children = match &section.content {
+        SectionContent::Captures(captures) => captures
+            .iter()
+            .map(|capture| {
+                make_symbol(
+                    capture.name.value.clone(),
+                    lsp_types::SymbolKind::VARIABLE,
+                    positions.range(capture.span),
+                    positions.range(capture.name.span),
+                    None,
+                )
+            })
+            .collect(),
+        SectionContent::KeyValues(_) | SectionContent::Asserts(_) => vec![],
+    };
+    make_symbol(
+        format!("[{}]", section.name.value),
+        lsp_types::SymbolKind::MODULE,
+        positions.range(section.span),
+        positions.range(section.name.span),
+        Some(children),
+    )
+}
+
+fn make_symbol(
+    name: String,
+    kind: lsp_types::SymbolKind,
+    range: lsp_types::Range,
+    selection_range: lsp_types::Range,
+    children: Option<Vec<lsp_types::DocumentSymbol>>,
+) -> lsp_types::DocumentSymbol {
+    // `deprecated` is superseded by `tags` but has to be set all the same.
+    #[allow(deprecated)]
+    lsp_types::DocumentSymbol {
+        name,
+        detail: None,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children,
+    }
+}
+
+/// Flat form of `symbol`, found in the document at `uri`, for `workspace/symbol`.
+pub fn symbol_information(
+    symbol: &lsp_types::DocumentSymbol,
+    uri: &lsp_types::Url,
+    container_name: Option<String>,
+) -> lsp_types::SymbolInformation {
+    // Same as in `make_symbol`.
+    #[allow(deprecated)]
+    lsp_types::SymbolInformation {
+        name: symbol.name.clone(),
+        kind: symbol.kind,
+        tags: None,
+        deprecated: None,
+        location: lsp_types::Location::new(uri.clone(), symbol.range),
+        container_name,
+    }
+}
@@ -0,0 +1,1576 @@
+// Line oriented parser for Hurl files. The grammar is described at
+// https://hurl.dev/docs/grammar.html; this parser follows it closely enough to build the tree in
+// `ast` but does not validate the meaning of queries, predicates or options.
+
+use std::cell::Cell;
+
+use crate::ast::{
+    Assert, Body, BodyKind, Capture, Entry, Filter, HurlFile, KeyValue, Pos, Predicate, Query,
+    Request, Response, Section, SectionContent, SectionKind, Shift, Span, Spanned, Value,
+};
+use crate::grammar;
+
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Shift for ParseError {
+    fn shift(&mut self, lines: isize) {
+        self.span.shift(lines);
+    }
+}
+
+impl ParseError {
+    fn new(span: Span, message: impl Into<String>) -> Self {
+        ParseError {
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+/// Parses a whole file. Syntax errors don't stop the parser: it records them, skips or
+/// truncates the offending construct and carries on, so the tree always covers as much of the
+/// file as possible.
+pub fn parse(text: &str) -> (HurlFile, Vec<ParseError>) {
+    parse_lines(&split_lines(text))
+}
+
+pub fn parse_lines(lines: &[Vec<char>]) -> (HurlFile, Vec<ParseError>) {
+    let parsed = parse_entries(lines, 0, |_| false);
+    (
+        HurlFile {
+            entries: parsed.entries,
+        },
+        parsed.errors,
+    )
+}
+
+pub struct ParsedEntries {
+    pub entries: Vec<Entry>,
+    /// For each entry, the last line the parser read to build it. Editing any line up to that
+    /// one may change the entry.
+    pub extents: Vec<usize>,
+    pub errors: Vec<ParseError>,
+    /// Line parsing stopped at, `lines.len()` when the end of the file was reached.
+    pub stop_line: usize,
+}
+
+/// Parses entries from line `start` onwards, stopping before the first entry whose request
+/// line satisfies `stop`.
+pub fn parse_entries(
+    lines: &[Vec<char>],
+    start: usize,
+    stop: impl Fn(usize) -> bool,
+) -> ParsedEntries {
+    let mut parser = Parser {
+        lines,
+        line: start,
+        errors: vec![],
+        furthest: Cell::new(start),
+    };
+    let mut entries = vec![];
+    let mut extents = vec![];
+    parser.skip_blank_lines();
+    while !parser.at_end() && !stop(parser.line) {
+        parser.furthest.set(parser.line);
+        entries.push(parser.entry());
+        parser.skip_blank_lines();
+        extents.push(parser.furthest.get().max(parser.line));
+    }
+    ParsedEntries {
+        entries,
+        extents,
+        errors: parser.errors,
+        stop_line: parser.line.min(lines.len()),
+    }
+}
+
+/// Lines of `text` split at the line breaks LSP knows: `\n`, `\r\n` and `\r`.
+pub fn split_lines(text: &str) -> Vec<Vec<char>> {
+    let mut lines = vec![];
+    let mut line = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                lines.push(std::mem::take(&mut line));
+            }
+            '\n' => lines.push(std::mem::take(&mut line)),
+            c => line.push(c),
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    Operator,
+    Quoted,
+    Regex,
+    Backtick,
+    Template,
+    Number,
+    Bytes,
+    MultilineStart,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    value: Value,
+    span: Span,
+}
+
+impl Token {
+    fn text(&self) -> &str {
+        match &self.value {
+            Value::Word(word) => word.as_str(),
+            _ => "",
+        }
+    }
+
+    fn spanned_value(&self) -> Spanned<Value> {
+        Spanned {
+            value: self.value.clone(),
+            span: self.span,
+        }
+    }
+
+    fn spanned_text(&self) -> Spanned<String> {
+        Spanned {
+            value: self.text().to_string(),
+            span: self.span,
+        }
+    }
+}
+
+struct Parser<'a> {
+    lines: &'a [Vec<char>],
+    line: usize,
+    errors: Vec<ParseError>,
+    /// Furthest line looked at by a scan running ahead of `line`.
+    furthest: Cell<usize>,
+}
+
+impl Parser<'_> {
+    fn error(&mut self, span: Span, message: impl Into<String>) {
+        self.errors.push(ParseError::new(span, message));
+    }
+
+    fn at_end(&self) -> bool {
+        self.line >= self.lines.len()
+    }
+
+    fn current(&self) -> &[char] {
+        &self.lines[self.line]
+    }
+
+    fn skip_blank_lines(&mut self) {
+        while !self.at_end() && is_blank(self.current()) {
+            self.line += 1;
+        }
+    }
+
+    /// End of the last non blank line before the current one.
+    fn previous_end(&self) -> Pos {
+        self.content_end_before(self.line)
+    }
+
+    /// End of the last non blank line before `line`.
+    fn content_end_before(&self, mut line: usize) -> Pos {
+        while line > 0 {
+            line -= 1;
+            if !is_blank(&self.lines[line]) {
+                return Pos::new(line, content_end(&self.lines[line]));
+            }
+        }
+        Pos::new(0, 0)
+    }
+
+    /// First line after `line` starting a section, a response or an entry. A construct missing
+    /// its terminator is cut there so it doesn't swallow the rest of the file.
+    fn recovery_line(&self, line: usize) -> usize {
+        let recovery_line = (line + 1..self.lines.len())
+            .find(|index| {
+                let chars = &self.lines[*index];
+                section_header(chars).is_some()
+                    || response_line(chars).is_some()
+                    || method_line(chars)
+            })
+            .unwrap_or(self.lines.len());
+        self.look_ahead(recovery_line);
+        recovery_line
+    }
+
+    fn look_ahead(&self, line: usize) {
+        self.furthest.set(self.furthest.get().max(line));
+    }
+
+    fn entry(&mut self) -> Entry {
+        let request = self.request();
+        self.skip_blank_lines();
+        let response = if !self.at_end() && response_line(self.current()).is_some() {
+            Some(self.response())
+        } else {
+            None
+        };
+        let end = match &response {
+            Some(response) => response.span.end,
+            None => request.span.end,
+        };
+        Entry {
+            span: Span::new(request.span.start, end),
+            request,
+            response,
+        }
+    }
+
+    fn request(&mut self) -> Request {
+        let line = self.line;
+        let chars = self.current();
+        let start = indent(chars);
+        let end = content_end(chars);
+        let method_end = word_end(chars, start, end);
+        let method = Spanned {
+            value: chars[start..method_end].iter().collect(),
+            span: Span::on_line(line, start, method_end),
+        };
+        let url_start = skip_whitespace(chars, method_end, end);
+        let url = Spanned {
+            value: chars[url_start..end].iter().collect::<String>(),
+            span: Span::on_line(line, url_start, end),
+        };
+        if url.value.is_empty() {
+            self.error(Span::on_line(line, start, end), "Expected URL after method");
+        }
+        self.line += 1;
+
+        let headers = self.headers();
+        let sections = self.sections();
+        let body = self.body();
+        Request {
+            span: Span::new(Pos::new(line, start), self.previous_end()),
+            method,
+            url,
+            headers,
+            sections,
+            body,
+        }
+    }
+
+    fn response(&mut self) -> Response {
+        let line = self.line;
+        let chars = self.current();
+        let start = indent(chars);
+        let end = content_end(chars);
+        let version_end = word_end(chars, start, end);
+        let version = Spanned {
+            value: chars[start..version_end].iter().collect(),
+            span: Span::on_line(line, start, version_end),
+        };
+        let status_start = skip_whitespace(chars, version_end, end);
+        let status_end = word_end(chars, status_start, end);
+        let status = Spanned {
+            value: chars[status_start..status_end].iter().collect::<String>(),
+            span: Span::on_line(line, status_start, status_end),
+        };
+        if status.value.is_empty() {
+            self.error(
+                Span::on_line(line, start, end),
+                "Expected status code after HTTP version",
+            );
+        } else if status_end < end {
+            self.error(
+                Span::on_line(line, status_end, end),
+                "Unexpected content after status code",
+            );
+        }
+        self.line += 1;
+
+        let headers = self.headers();
+        let sections = self.sections();
+        let body = self.body();
+        Response {
+            span: Span::new(Pos::new(line, start), self.previous_end()),
+            version,
+            status,
+            headers,
+            sections,
+            body,
+        }
+    }
+
+    fn headers(&mut self) -> Vec<KeyValue> {
+        let mut headers = vec![];
+        loop {
+            self.skip_blank_lines();
+            if self.at_end() {
+                break;
+            }
+            let chars = self.current();
+            if section_header(chars).is_some()
+                || response_line(chars).is_some()
+                || body_start(chars).is_some()
+            {
+                break;
+            }
+            match key_value(chars, self.line) {
+                Some(header) => {
+                    headers.push(header);
+                    self.line += 1;
+                }
+                None => break,
+            }
+        }
+        headers
+    }
+
+    fn sections(&mut self) -> Vec<Section> {
+        let mut sections = vec![];
+        loop {
+            self.skip_blank_lines();
+            if self.at_end() || section_header(self.current()).is_none() {
+                break;
+            }
+            if let Some(section) = self.section() {
+                sections.push(section);
+            }
+        }
+        sections
+    }
+
+    fn section(&mut self) -> Option<Section> {
+        let line = self.line;
+        let chars = self.current();
+        let (name, start, end) = section_header(chars).unwrap();
+        let header_span = Span::on_line(line, start, end);
+        let trailing = content_end(chars);
+        if trailing > end {
+            let trailing_start = skip_whitespace(chars, end, trailing);
+            self.error(
+                Span::on_line(line, trailing_start, trailing),
+                "Expected newline after section header",
+            );
+        }
+        let kind = SectionKind::from_name(&name);
+        if self.current()[end - 1] != ']' {
+            self.error(header_span, "Expected `]` after section name");
+        } else if kind.is_none() {
+            self.error(header_span, format!("Unknown section [{}]", name));
+        }
+        self.line += 1;
+
+        let mut key_values = vec![];
+        let mut captures = vec![];
+        let mut asserts = vec![];
+        loop {
+            self.skip_blank_lines();
+            if self.at_end() || self.at_section_end() {
+                break;
+            }
+            match kind {
+                None => self.line += 1,
+                Some(SectionKind::Captures) => {
+                    if let Some(capture) = self.capture() {
+                        captures.push(capture);
+                    }
+                }
+                Some(SectionKind::Asserts) => {
+                    if let Some(assert) = self.assert() {
+                        asserts.push(assert);
+                    }
+                }
+                Some(_) => {
+                    match key_value(self.current(), self.line) {
+                        Some(key_value) => key_values.push(key_value),
+                        None => self.error(self.current_line_span(), "Expected `key: value`"),
+                    }
+                    self.line += 1;
+                }
+            }
+        }
+        let kind = kind?;
+        let content = match kind {
+            SectionKind::Captures => SectionContent::Captures(captures),
+            SectionKind::Asserts => SectionContent::Asserts(asserts),
+            _ => SectionContent::KeyValues(key_values),
+        };
+        Some(Section {
+            span: Span::new(header_span.start, self.previous_end()),
+            name: Spanned {
+                value: name,
+                span: header_span,
+            },
+            kind,
+            content,
+        })
+    }
+
+    /// Whether the current line starts something that cannot be part of a section.
+    fn at_section_end(&self) -> bool {
+        let chars = self.current();
+        if section_header(chars).is_some()
+            || response_line(chars).is_some()
+            || body_start(chars).is_some()
+        {
+            return true;
+        }
+        key_value(chars, self.line).is_none() && method_line(chars)
+    }
+
+    fn current_line_span(&self) -> Span {
+        let chars = self.current();
+        Span::on_line(self.line, indent(chars), content_end(chars))
+    }
+
+    fn capture(&mut self) -> Option<Capture> {
+        let line = self.line;
+        let chars = self.current().to_vec();
+        let (key_end, value_start) = match key_end(&chars) {
+            Some(columns) => columns,
+            None => {
+                self.error(self.current_line_span(), "Expected `name: query`");
+                self.line += 1;
+                return None;
+            }
+        };
+        let start = indent(&chars);
+        let name = Spanned {
+            value: chars[start..key_end].iter().collect::<String>(),
+            span: Span::on_line(line, start, key_end),
+        };
+        let tokens = lex(
+            &chars,
+            value_start,
+            content_end(&chars),
+            line,
+            &mut self.errors,
+        );
+        let mut index = 0;
+        let query = self.query(
+            &tokens,
+            &mut index,
+            Span::on_line(line, value_start, value_start),
+        );
+        let filters = filters(&tokens, &mut index);
+        if let (Some(_), Some(token)) = (&query, tokens.get(index)) {
+            self.error(token.span, "Unexpected token in capture");
+        }
+        self.line += 1;
+        Some(Capture {
+            span: Span::new(name.span.start, self.previous_end()),
+            name,
+            query,
+            filters,
+        })
+    }
+
+    fn assert(&mut self) -> Option<Assert> {
+        let line = self.line;
+        let chars = self.current().to_vec();
+        let start = indent(&chars);
+        let end = content_end(&chars);
+        let tokens = lex(&chars, start, end, line, &mut self.errors);
+        let mut index = 0;
+        let query = match self.query(&tokens, &mut index, Span::on_line(line, start, start)) {
+            Some(query) => query,
+            None => {
+                self.line += 1;
+                return None;
+            }
+        };
+        let filters = filters(&tokens, &mut index);
+        let predicate = self.predicate(&tokens, &mut index, Span::on_line(line, end, end));
+        if let (Some(_), Some(token)) = (&predicate, tokens.get(index)) {
+            self.error(token.span, "Unexpected token in assert");
+        }
+        self.line += 1;
+
+        let assert_end = predicate
+            .as_ref()
+            .map(|predicate| predicate.span.end)
+            .or(filters.last().map(|filter| filter.span.end))
+            .unwrap_or(query.span.end);
+        Some(Assert {
+            span: Span::new(Pos::new(line, start), assert_end),
+            query,
+            filters,
+            predicate,
+        })
+    }
+
+    fn query(&mut self, tokens: &[Token], index: &mut usize, empty_span: Span) -> Option<Query> {
+        let name = match tokens.get(*index) {
+            Some(token) if token.kind == TokenKind::Word => token.spanned_text(),
+            Some(token) => {
+                self.error(token.span, "Expected query");
+                return None;
+            }
+            None => {
+                self.error(empty_span, "Expected query");
+                return None;
+            }
+        };
+        *index += 1;
+        let argument = match tokens.get(*index) {
+            Some(token)
+                if matches!(
+                    token.kind,
+                    TokenKind::Quoted | TokenKind::Regex | TokenKind::Backtick
+                ) =>
+            {
+                *index += 1;
+                Some(token.spanned_value())
+            }
+            _ => None,
+        };
+        let end = argument
+            .as_ref()
+            .map(|argument| argument.span.end)
+            .unwrap_or(name.span.end);
+        Some(Query {
+            span: Span::new(name.span.start, end),
+            name,
+            argument,
+        })
+    }
+
+    fn predicate(
+        &mut self,
+        tokens: &[Token],
+        index: &mut usize,
+        empty_span: Span,
+    ) -> Option<Predicate> {
+        let not = match tokens.get(*index) {
+            Some(token) if token.kind == TokenKind::Word && token.text() == "not" => {
+                *index += 1;
+                Some(token.span)
+            }
+            _ => None,
+        };
+        let name = match tokens.get(*index) {
+            Some(token) if matches!(token.kind, TokenKind::Word | TokenKind::Operator) => {
+                *index += 1;
+                token.spanned_text()
+            }
+            Some(token) => {
+                self.error(token.span, "Expected predicate");
+                return None;
+            }
+            None => {
+                self.error(empty_span, "Expected predicate");
+                return None;
+            }
+        };
+        let value = match tokens.get(*index) {
+            Some(token) if token.kind == TokenKind::MultilineStart => {
+                *index += 1;
+                self.line += 1;
+                Some(self.multiline_value(token.span.start))
+            }
+            Some(token) => {
+                *index += 1;
+                Some(token.spanned_value())
+            }
+            None => None,
+        };
+        let start = not.map(|span| span.start).unwrap_or(name.span.start);
+        let end = value
+            .as_ref()
+            .map(|value| value.span.end)
+            .unwrap_or(name.span.end);
+        Some(Predicate {
+            span: Span::new(start, end),
+            not,
+            name,
+            value,
+        })
+    }
+
+    /// Reads the lines of a ``` string up to its closing fence. The current line must be the
+    /// first line after the opening fence; on return it is the closing fence, or the last line
+    /// of the string when the fence is missing.
+    fn multiline_value(&mut self, start: Pos) -> Spanned<Value> {
+        // Anything goes inside the string, so the fence is looked for before falling back on
+        // the lines that would start a new construct.
+        let fence = (self.line..self.lines.len()).find(|line| is_fence(&self.lines[*line]));
+        self.look_ahead(fence.unwrap_or(self.lines.len()));
+        if let Some(fence) = fence {
+            let content: Vec<String> = self.lines[self.line..fence]
+                .iter()
+                .map(|chars| chars.iter().collect())
+                .collect();
+            self.line = fence;
+            return Spanned {
+                value: Value::MultilineString(content.join("\n")),
+                span: Span::new(start, Pos::new(fence, content_end(self.current()))),
+            };
+        }
+        let recovery_line = self.recovery_line(start.line);
+        let content: Vec<String> = self.lines[self.line.min(recovery_line)..recovery_line]
+            .iter()
+            .map(|chars| chars.iter().collect())
+            .collect();
+        self.error(
+            Span::on_line(start.line, start.column, start.column + 3),
+            "Unterminated multiline string, expected ```",
+        );
+        let end = self.content_end_before(recovery_line).max(start);
+        self.line = end.line;
+        Spanned {
+            value: Value::MultilineString(content.join("\n")),
+            span: Span::new(start, end),
+        }
+    }
+
+    fn body(&mut self) -> Option<Body> {
+        self.skip_blank_lines();
+        if self.at_end() {
+            return None;
+        }
+        let kind = body_start(self.current())?;
+        let line = self.line;
+        let start = indent(self.current());
+        let end = match kind {
+            BodyKind::Json => self.json_body_end(line, start),
+            BodyKind::Xml => self.xml_body_end(line, start),
+            BodyKind::MultilineString => {
+                self.line += 1;
+                let value = self.multiline_value(Pos::new(line, start));
+                Ok(value.span.end)
+            }
+            BodyKind::OnelineString => self.inline_body_end(line, start, '`', '`'),
+            BodyKind::QuotedString => self.inline_body_end(line, start, '"', '"'),
+            BodyKind::Base64 | BodyKind::Hex | BodyKind::File => {
+                self.inline_body_end(line, start, ',', ';')
+            }
+        };
+        let end = match end {
+            Ok(end) => end,
+            Err(error) => {
+                let end = match kind {
+                    BodyKind::Json | BodyKind::Xml => self
+                        .content_end_before(self.recovery_line(line))
+                        .max(error.span.end),
+                    _ => error.span.end,
+                };
+                self.errors.push(error);
+                end
+            }
+        };
+        let trailing = content_end(&self.lines[end.line]);
+        if trailing > end.column {
+            let trailing_start = skip_whitespace(&self.lines[end.line], end.column, trailing);
+            if trailing_start < trailing {
+                self.error(
+                    Span::on_line(end.line, trailing_start, trailing),
+                    "Unexpected content after body",
+                );
+            }
+        }
+        self.line = end.line + 1;
+        Some(Body {
+            span: Span::new(Pos::new(line, start), end),
+            kind,
+        })
+    }
+
+    /// Finds the end of a string body delimited by `open` and `close` on a single line.
+    fn inline_body_end(
+        &self,
+        line: usize,
+        start: usize,
+        open: char,
+        close: char,
+    ) -> Result<Pos, ParseError> {
+        let chars = &self.lines[line];
+        let from = chars[start..]
+            .iter()
+            .position(|c| *c == open)
+            .map(|i| start + i + 1)
+            .unwrap_or(start + 1);
+        let mut column = from;
+        while column < chars.len() {
+            match chars[column] {
+                '\\' => column += 2,
+                c if c == close => return Ok(Pos::new(line, column + 1)),
+                _ => column += 1,
+            }
+        }
+        Err(ParseError::new(
+            Span::on_line(line, start, content_end(chars)),
+            format!("Unterminated body, expected {}", delimiter_name(close)),
+        ))
+    }
+
+    fn json_body_end(&self, line: usize, start: usize) -> Result<Pos, ParseError> {
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut escaped = false;
+        for (line_index, chars) in self.lines.iter().enumerate().skip(line) {
+            let from = if line_index == line { start } else { 0 };
+            for (column, c) in chars.iter().enumerate().skip(from) {
+                if in_string {
+                    if escaped {
+                        escaped = false;
+                    } else if *c == '\\' {
+                        escaped = true;
+                    } else if *c == '"' {
+                        in_string = false;
+                    }
+                    continue;
+                }
+                match c {
+                    '"' => in_string = true,
+                    '{' | '[' => depth += 1,
+                    '}' | ']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            self.look_ahead(line_index);
+                            return Ok(Pos::new(line_index, column + 1));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        self.look_ahead(self.lines.len());
+        Err(ParseError::new(
+            Span::on_line(line, start, start + 1),
+            "Unterminated JSON body",
+        ))
+    }
+
+    fn xml_body_end(&self, line: usize, start: usize) -> Result<Pos, ParseError> {
+        let mut depth = 0;
+        // The char following the `<` of the markup being read, `<` itself until it is known.
+        let mut markup: Option<char> = None;
+        let mut comment = false;
+        let mut previous = [' ', ' '];
+        for (line_index, chars) in self.lines.iter().enumerate().skip(line) {
+            let from = if line_index == line { start } else { 0 };
+            for (column, c) in chars.iter().enumerate().skip(from) {
+                match markup {
+                    None if *c == '<' => markup = Some('<'),
+                    None => {}
+                    Some('<') if *c != '>' => {
+                        markup = Some(*c);
+                        comment = *c == '!' && chars[column + 1..].starts_with(&['-', '-']);
+                    }
+                    Some(kind) if *c == '>' && (!comment || previous == ['-', '-']) => {
+                        match kind {
+                            '?' | '!' => {}
+                            '/' => depth -= 1,
+                            _ if previous[1] == '/' => {}
+                            _ => depth += 1,
+                        }
+                        markup = None;
+                        if depth == 0 && !matches!(kind, '?' | '!') {
+                            self.look_ahead(line_index);
+                            return Ok(Pos::new(line_index, column + 1));
+                        }
+                    }
+                    Some(_) => {}
+                }
+                previous = [previous[1], *c];
+            }
+        }
+        self.look_ahead(self.lines.len());
+        Err(ParseError::new(
+            Span::on_line(line, start, start + 1),
+            "Unterminated XML body",
+        ))
+    }
+}
+
+/// Chain of filters between a query and its predicate. Filter names are needed to know where the
+/// chain stops and the predicate starts; their arguments are checked later by the diagnostics.
+fn filters(tokens: &[Token], index: &mut usize) -> Vec<Filter> {
+    let mut filters = vec![];
+    while let Some(token) = tokens.get(*index) {
+        if token.kind != TokenKind::Word || grammar::filter_arguments(token.text()).is_none() {
+            break;
+        }
+        *index += 1;
+        let mut arguments = vec![];
+        while let Some(argument) = tokens.get(*index) {
+            if !matches!(
+                argument.kind,
+                TokenKind::Quoted
+                    | TokenKind::Regex
+                    | TokenKind::Backtick
+                    | TokenKind::Template
+                    | TokenKind::Number
+            ) {
+                break;
+            }
+            arguments.push(argument.spanned_value());
+            *index += 1;
+        }
+        let end = arguments
+            .last()
+            .map(|argument| argument.span.end)
+            .unwrap_or(token.span.end);
+        filters.push(Filter {
+            span: Span::new(token.span.start, end),
+            name: token.spanned_text(),
+            arguments,
+        });
+    }
+    filters
+}
+
+/// Splits `chars[start..end]` into the tokens used by captures and asserts. An unterminated
+/// literal is reported and runs up to `end`.
+fn lex(
+    chars: &[char],
+    start: usize,
+    end: usize,
+    line: usize,
+    errors: &mut Vec<ParseError>,
+) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut column = start;
+    while column < end {
+        let c = chars[column];
+        if c.is_whitespace() {
+            column += 1;
+            continue;
+        }
+        let token_start = column;
+        let (kind, value) = match c {
+            '`' if chars[column..end].starts_with(&['`', '`', '`']) => {
+                // The fence may carry a language hint such as ```json, part of the same token.
+                column += 3;
+                while column < end && chars[column].is_alphanumeric() {
+                    column += 1;
+                }
+                (
+                    TokenKind::MultilineStart,
+                    Value::MultilineString(String::new()),
+                )
+            }
+            '"' | '`' | '/' => {
+                let (content, close) = delimited(chars, column, end, c);
+                column = match close {
+                    Some(close) => close + 1,
+                    None => {
+                        errors.push(ParseError::new(
+                            Span::on_line(line, column, end),
+                            format!("Unterminated literal, expected {}", delimiter_name(c)),
+                        ));
+                        end
+                    }
+                };
+                match c {
+                    '"' => (TokenKind::Quoted, Value::String(content)),
+                    '`' => (TokenKind::Backtick, Value::String(content)),
+                    _ => (TokenKind::Regex, Value::Regex(content)),
+                }
+            }
+            '{' if chars[column..end].starts_with(&['{', '{']) => {
+                let close = (column + 2..end.saturating_sub(1))
+                    .find(|i| chars[*i] == '}' && chars[*i + 1] == '}');
+                let name: String = chars[column + 2..close.unwrap_or(end)].iter().collect();
+                column = match close {
+                    Some(close) => close + 2,
+                    None => {
+                        errors.push(ParseError::new(
+                            Span::on_line(line, column, end),
+                            "Unterminated template, expected `}}`",
+                        ));
+                        end
+                    }
+                };
+                (
+                    TokenKind::Template,
+                    Value::Template(name.trim().to_string()),
+                )
+            }
+            '=' | '!' | '<' | '>' => {
+                // Take every operator char so a typo like `===` is reported as a whole.
+                while column < end && ['=', '!', '<', '>'].contains(&chars[column]) {
+                    column += 1;
+                }
+                let operator: String = chars[token_start..column].iter().collect();
+                (TokenKind::Operator, Value::Word(operator))
+            }
+            _ => {
+                column = word_end(chars, column, end);
+                let word: String = chars[token_start..column].iter().collect();
+                if ["base64,", "hex,", "file,"]
+                    .iter()
+                    .any(|prefix| word.starts_with(prefix))
+                {
+                    if let Some(semicolon) = (token_start..end).find(|i| chars[*i] == ';') {
+                        column = semicolon + 1;
+                    }
+                    let bytes: String = chars[token_start..column].iter().collect();
+                    (TokenKind::Bytes, Value::Bytes(bytes))
+                } else if let Ok(integer) = word.parse::<i64>() {
+                    (TokenKind::Number, Value::Integer(integer))
+                } else if let (true, Ok(float)) = (
+                    word.starts_with(|c: char| c.is_ascii_digit() || c == '-'),
+                    word.parse::<f64>(),
+                ) {
+                    (TokenKind::Number, Value::Float(float))
+                } else {
+                    match word.as_str() {
+                        "true" => (TokenKind::Word, Value::Bool(true)),
+                        "false" => (TokenKind::Word, Value::Bool(false)),
+                        "null" => (TokenKind::Word, Value::Null),
+                        _ => (TokenKind::Word, Value::Word(word)),
+                    }
+                }
+            }
+        };
+        tokens.push(Token {
+            kind,
+            value,
+            span: Span::on_line(line, token_start, column),
+        });
+    }
+    tokens
+}
+
+/// Reads a literal delimited by `delimiter` starting at `start`, handling backslash escapes.
+/// Returns the unescaped content and the column of the closing delimiter, if there is one.
+fn delimited(chars: &[char], start: usize, end: usize, delimiter: char) -> (String, Option<usize>) {
+    let mut content = String::new();
+    let mut column = start + 1;
+    while column < end {
+        let c = chars[column];
+        if c == delimiter {
+            return (content, Some(column));
+        }
+        if c == '\\' && column + 1 < end {
+            column += 1;
+            let escaped = chars[column];
+            match escaped {
+                'n' if delimiter != '/' => content.push('\n'),
+                't' if delimiter != '/' => content.push('\t'),
+                'r' if delimiter != '/' => content.push('\r'),
+                c if c == delimiter => content.push(c),
+                '\\' if delimiter != '/' => content.push('\\'),
+                _ => {
+                    content.push('\\');
+                    content.push(escaped);
+                }
+            }
+        } else {
+            content.push(c);
+        }
+        column += 1;
+    }
+    (content, None)
+}
+
+/// Name of the delimiter closing a literal in messages. A backtick can't be quoted between
+/// backticks without reading as a ``` fence.
+fn delimiter_name(delimiter: char) -> String {
+    match delimiter {
+        '`' => "closing backtick".to_string(),
+        _ => format!("`{}`", delimiter),
+    }
+}
+
+pub fn indent(chars: &[char]) -> usize {
+    chars
+        .iter()
+        .position(|c| !c.is_whitespace())
+        .unwrap_or(chars.len())
+}
+
+pub fn skip_whitespace(chars: &[char], start: usize, end: usize) -> usize {
+    (start..end)
+        .find(|i| !chars[*i].is_whitespace())
+        .unwrap_or(end)
+}
+
+fn word_end(chars: &[char], start: usize, end: usize) -> usize {
+    (start..end)
+        .find(|i| chars[*i].is_whitespace())
+        .unwrap_or(end)
+}
+
+/// Column where the meaningful content of a line stops: before a trailing comment and trailing
+/// whitespace. Never before the indentation.
+pub fn content_end(chars: &[char]) -> usize {
+    let mut in_quotes = false;
+    let mut in_backticks = false;
+    let mut end = chars.len();
+    let mut column = 0;
+    while column < chars.len() {
+        match chars[column] {
+            '\\' => column += 1,
+            '"' if !in_backticks => in_quotes = !in_quotes,
+            '`' if !in_quotes => in_backticks = !in_backticks,
+            '#' if !in_quotes && !in_backticks => {
+                end = column;
+                break;
+            }
+            _ => {}
+        }
+        column += 1;
+    }
+    while end > 0 && chars[end - 1].is_whitespace() {
+        end -= 1;
+    }
+    end.max(indent(chars))
+}
+
+fn is_blank(chars: &[char]) -> bool {
+    content_end(chars) <= indent(chars)
+}
+
+fn is_fence(chars: &[char]) -> bool {
+    let start = indent(chars);
+    let end = content_end(chars);
+    chars[start..end] == ['`', '`', '`']
+}
+
+/// Returns the name of a `[Name]` section header with the columns of the brackets. The name
+/// must start with an uppercase letter so a JSON array such as `[true]` is not mistaken for a
+/// section. Content following the header on the same line is left to the caller.
+fn section_header(chars: &[char]) -> Option<(String, usize, usize)> {
+    let start = indent(chars);
+    if chars.get(start) != Some(&'[') || !chars.get(start + 1)?.is_ascii_uppercase() {
+        return None;
+    }
+    let name_end = (start + 1..chars.len())
+        .find(|i| !chars[*i].is_ascii_alphabetic())
+        .unwrap_or(chars.len());
+    let end = match chars.get(name_end) {
+        Some(']') => name_end + 1,
+        // A header still being typed: no JSON body starts with a bare word.
+        _ if name_end >= content_end(chars) => name_end,
+        _ => return None,
+    };
+    Some((chars[start + 1..name_end].iter().collect(), start, end))
+}
+
+/// Returns the span of the version token when the line is a `HTTP <status>` response line.
+fn response_line(chars: &[char]) -> Option<(usize, usize)> {
+    let start = indent(chars);
+    let end = content_end(chars);
+    let version_end = word_end(chars, start, end);
+    let version = &chars[start..version_end];
+    if version.starts_with(&['H', 'T', 'T', 'P']) && (version.len() == 4 || version[4] == '/') {
+        Some((start, version_end))
+    } else {
+        None
+    }
+}
+
+/// Whether the line looks like the `METHOD url` line starting a new entry: an uppercase method
+/// followed by anything, or a malformed method such as `post` or `G3T` followed by a single
+/// URL-like word, which no section line can be.
+fn method_line(chars: &[char]) -> bool {
+    let start = indent(chars);
+    let end = content_end(chars);
+    let method_end = word_end(chars, start, end);
+    if method_end <= start || method_end >= end {
+        return false;
+    }
+    let method = &chars[start..method_end];
+    if method.iter().all(|c| c.is_ascii_uppercase()) {
+        return true;
+    }
+    let url_start = skip_whitespace(chars, method_end, end);
+    let url: String = chars[url_start..end].iter().collect();
+    method.iter().all(|c| c.is_ascii_alphanumeric())
+        && !url.contains(char::is_whitespace)
+        && (url.contains("://") || url.starts_with("{{"))
+}
+
+fn body_start(chars: &[char]) -> Option<BodyKind> {
+    let start = indent(chars);
+    let rest = &chars[start..];
+    if rest.starts_with(&['`', '`', '`']) {
+        return Some(BodyKind::MultilineString);
+    }
+    let kind = match rest.first() {
+        Some('{') if !rest.starts_with(&['{', '{']) => BodyKind::Json,
+        Some('[') if section_header(chars).is_none() => BodyKind::Json,
+        Some('<') => BodyKind::Xml,
+        Some('`') => BodyKind::OnelineString,
+        Some('"') => BodyKind::QuotedString,
+        _ => {
+            let rest: String = rest.iter().collect();
+            if rest.starts_with("base64,") {
+                BodyKind::Base64
+            } else if rest.starts_with("hex,") {
+                BodyKind::Hex
+            } else if rest.starts_with("file,") {
+                BodyKind::File
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(kind)
+}
+
+/// Finds the colon separating a key from its value. Returns the column ending the key and the
+/// column starting the value.
+fn key_end(chars: &[char]) -> Option<(usize, usize)> {
+    let start = indent(chars);
+    let end = content_end(chars);
+    let mut column = start;
+    while column < end {
+        match chars[column] {
+            ':' if column > start => {
+                return Some((column, skip_whitespace(chars, column + 1, end)));
+            }
+            '\\' => column += 2,
+            '{' if chars[column..end].starts_with(&['{', '{']) => {
+                column = (column + 2..end.saturating_sub(1))
+                    .find(|i| chars[*i] == '}' && chars[*i + 1] == '}')
+                    .map(|i| i + 2)?;
+            }
+            c if c.is_alphanumeric() || "_-.[]@$".contains(c) => column += 1,
+            _ => return None,
+        }
+    }
+    None
+}
+
+fn key_value(chars: &[char], line: usize) -> Option<KeyValue> {
+    let (key_end, value_start) = key_end(chars)?;
+    let start = indent(chars);
+    let end = content_end(chars).max(value_start);
+    Some(KeyValue {
+        span: Span::on_line(line, start, end),
+        key: Spanned {
+            value: chars[start..key_end].iter().collect(),
+            span: Span::on_line(line, start, key_end),
+        },
+        value: Spanned {
+            value: chars[value_start..end].iter().collect(),
+            span: Span::on_line(line, value_start, end),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tree of `text`, which must parse without errors.
+    fn parse_valid(text: &str) -> HurlFile {
+        let (tree, errors) = parse(text);
+        assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+        tree
+    }
+
+    fn errors(text: &str) -> Vec<(Span, String)> {
+        parse(text)
+            .1
+            .into_iter()
+            .map(|error| (error.span, error.message))
+            .collect()
+    }
+
+    fn asserts(entry: &Entry) -> &[Assert] {
+        entry
+            .sections()
+            .find_map(|section| match &section.content {
+                SectionContent::Asserts(asserts) => Some(asserts.as_slice()),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    fn predicate_value(assert: &Assert) -> &Value {
+        &assert
+            .predicate
+            .as_ref()
+            .unwrap()
+            .value
+            .as_ref()
+            .unwrap()
+            .value
+    }
+
+    #[test]
+    fn split_lines_at_every_line_break() {
+        let lines: Vec<String> = split_lines("a\nb\r\nc\rd\n")
+            .iter()
+            .map(|line| line.iter().collect())
+            .collect();
+        assert_eq!(lines, ["a", "b", "c", "d", ""]);
+    }
+
+    #[test]
+    fn request_with_headers() {
+        let tree = parse_valid(
+            "GET https://example.org # home\nAccept: text/html\nUser-Agent: {{agent}}\n",
+        );
+        let request = &tree.entries[0].request;
+        assert_eq!(request.method.value, "GET");
+        assert_eq!(request.method.span, Span::on_line(0, 0, 3));
+        assert_eq!(request.url.value, "https://example.org");
+        assert_eq!(request.url.span, Span::on_line(0, 4, 23));
+        let headers: Vec<(&str, &str)> = request
+            .headers
+            .iter()
+            .map(|header| (header.key.value.as_str(), header.value.value.as_str()))
+            .collect();
+        assert_eq!(
+            headers,
+            [("Accept", "text/html"), ("User-Agent", "{{agent}}")]
+        );
+        assert!(tree.entries[0].response.is_none());
+    }
+
+    #[test]
+    fn entries_own_lines_up_to_the_next_request() {
+        let tree = parse_valid("# first\nGET https://a.org\n\nHTTP 200\n\n\nPOST https://b.org\n");
+        assert_eq!(tree.entries.len(), 2);
+        assert_eq!(
+            tree.entries[0].span,
+            Span::new(Pos::new(1, 0), Pos::new(3, 8))
+        );
+        assert_eq!(tree.entry_at(5).unwrap().request.url.value, "https://a.org");
+        assert_eq!(tree.entry_at(6).unwrap().request.url.value, "https://b.org");
+    }
+
+    #[test]
+    fn response_with_version_and_status() {
+        let tree = parse_valid("GET https://example.org\nHTTP/1.1 404\nContent-Type: text/plain\n");
+        let response = tree.entries[0].response.as_ref().unwrap();
+        assert_eq!(response.version.value, "HTTP/1.1");
+        assert_eq!(response.status.value, "404");
+        assert_eq!(response.status.span, Span::on_line(1, 9, 12));
+        assert_eq!(response.headers[0].key.value, "Content-Type");
+    }
+
+    #[test]
+    fn request_and_response_line_errors() {
+        assert_eq!(
+            errors("GET\n"),
+            [(
+                Span::on_line(0, 0, 3),
+                "Expected URL after method".to_string()
+            )]
+        );
+        assert_eq!(
+            errors("GET https://example.org\nHTTP\n"),
+            [(
+                Span::on_line(1, 0, 4),
+                "Expected status code after HTTP version".to_string()
+            )]
+        );
+        assert_eq!(
+            errors("GET https://example.org\nHTTP 200 OK\n"),
+            [(
+                Span::on_line(1, 8, 11),
+                "Unexpected content after status code".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn key_value_sections() {
+        let tree = parse_valid(
+            "GET https://example.org\n[QueryStringParams]\nq: hurl\n\n[Options]\nvariable: id=1\n",
+        );
+        let sections: Vec<&Section> = tree.entries[0].sections().collect();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].kind, SectionKind::QueryStringParams);
+        assert_eq!(sections[0].name.value, "QueryStringParams");
+        assert_eq!(sections[0].span, Span::new(Pos::new(1, 0), Pos::new(2, 7)));
+        match &sections[1].content {
+            SectionContent::KeyValues(options) => {
+                assert_eq!(options[0].key.value, "variable");
+                assert_eq!(options[0].value.value, "id=1");
+            }
+            content => panic!("unexpected content {:?}", content),
+        }
+    }
+
+    #[test]
+    fn malformed_methods_end_sections() {
+        for section in ["[Asserts]\nstatus == 200", "[Options]\nverbose: true"] {
+            let text = format!(
+                "GET https://a.org\nHTTP 200\n{}\n\npost https://b.org\nHTTP 200\nG3T {{{{host}}}}/c\n",
+                section
+            );
+            let tree = parse_valid(&text);
+            let methods: Vec<&str> = tree
+                .entries
+                .iter()
+                .map(|entry| entry.request.method.value.as_str())
+                .collect();
+            assert_eq!(methods, ["GET", "post", "G3T"]);
+            assert_eq!(tree.entries[0].span.end.line, 3);
+            assert!(tree.entries[1].response.is_some());
+        }
+    }
+
+    #[test]
+    fn section_errors() {
+        assert_eq!(
+            errors("GET https://example.org\n[Foo]\na: b\n[Options\n[Cookies] x\nnot a pair\n"),
+            [
+                (Span::on_line(1, 0, 5), "Unknown section [Foo]".to_string()),
+                (
+                    Span::on_line(3, 0, 8),
+                    "Expected `]` after section name".to_string()
+                ),
+                (
+                    Span::on_line(4, 10, 11),
+                    "Expected newline after section header".to_string()
+                ),
+                (Span::on_line(5, 0, 10), "Expected `key: value`".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn captures_with_queries_and_filters() {
+        let tree = parse_valid(
+            "GET https://example.org\nHTTP 200\n[Captures]\nid: jsonpath \"$.id\" toInt\ncsrf: xpath \"//input/@value\" regex /(\\w+)/ nth 0\n",
+        );
+        let captures = match &tree.entries[0].sections().next().unwrap().content {
+            SectionContent::Captures(captures) => captures,
+            content => panic!("unexpected content {:?}", content),
+        };
+        assert_eq!(captures[0].name.value, "id");
+        let query = captures[0].query.as_ref().unwrap();
+        assert_eq!(query.name.value, "jsonpath");
+        assert_eq!(
+            query.argument.as_ref().unwrap().value,
+            Value::String("$.id".to_string())
+        );
+        assert_eq!(captures[0].filters[0].name.value, "toInt");
+        let filters: Vec<(&str, Vec<&Value>)> = captures[1]
+            .filters
+            .iter()
+            .map(|filter| {
+                let arguments = filter.arguments.iter().map(|argument| &argument.value);
+                (filter.name.value.as_str(), arguments.collect())
+            })
+            .collect();
+        assert_eq!(
+            filters,
+            [
+                ("regex", vec![&Value::Regex("(\\w+)".to_string())]),
+                ("nth", vec![&Value::Integer(0)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn capture_errors() {
+        assert_eq!(
+            errors("GET https://example.org\nHTTP 200\n[Captures]\nid\nname: \"x\"\ncount: body count extra\n"),
+            [
+                (Span::on_line(3, 0, 2), "Expected `name: query`".to_string()),
+                (Span::on_line(4, 6, 9), "Expected query".to_string()),
+                (
+                    Span::on_line(5, 18, 23),
+                    "Unexpected token in capture".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn asserts_with_predicate_values() {
+        let tree = parse_valid(
+            "GET https://example.org\nHTTP 200\n[Asserts]\n\
+             status == 200\n\
+             jsonpath \"$.price\" < 9.5\n\
+             jsonpath \"$.valid\" == true\n\
+             jsonpath \"$.deleted\" == null\n\
+             header \"Server\" matches /^nginx/\n\
+             jsonpath \"$.id\" == {{id}}\n\
+             bytes startsWith hex,cafe;\n\
+             body not contains \"error\"\n\
+             jsonpath \"$.items\" count >= 1\n\
+             jsonpath \"$.name\" exists\n",
+        );
+        let asserts = asserts(&tree.entries[0]);
+        let values: Vec<&Value> = asserts[..7].iter().map(predicate_value).collect();
+        assert_eq!(
+            values,
+            [
+                &Value::Integer(200),
+                &Value::Float(9.5),
+                &Value::Bool(true),
+                &Value::Null,
+                &Value::Regex("^nginx".to_string()),
+                &Value::Template("id".to_string()),
+                &Value::Bytes("hex,cafe;".to_string()),
+            ]
+        );
+        let predicate = asserts[7].predicate.as_ref().unwrap();
+        assert_eq!(predicate.not, Some(Span::on_line(10, 5, 8)));
+        assert_eq!(predicate.name.value, "contains");
+        assert_eq!(asserts[8].filters[0].name.value, "count");
+        assert_eq!(asserts[8].predicate.as_ref().unwrap().name.value, ">=");
+        assert!(asserts[9].predicate.as_ref().unwrap().value.is_none());
+    }
+
+    #[test]
+    fn assert_errors() {
+        assert_eq!(
+            errors(
+                "GET https://example.org\nHTTP 200\n[Asserts]\nstatus\n== 1\nstatus == 200 201\n"
+            ),
+            [
+                (Span::on_line(3, 6, 6), "Expected predicate".to_string()),
+                (Span::on_line(4, 0, 2), "Expected query".to_string()),
+                (
+                    Span::on_line(5, 14, 17),
+                    "Unexpected token in assert".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_literals() {
+        assert_eq!(
+            errors("GET https://example.org\nHTTP 200\n[Asserts]\nbody == \"abc\nbody == {{name\nbody == `abc\n"),
+            [
+                (
+                    Span::on_line(3, 8, 12),
+                    "Unterminated literal, expected `\"`".to_string()
+                ),
+                (
+                    Span::on_line(4, 8, 14),
+                    "Unterminated template, expected `}}`".to_string()
+                ),
+                (
+                    Span::on_line(5, 8, 12),
+                    "Unterminated literal, expected closing backtick".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn multiline_predicate_value() {
+        let tree = parse_valid(
+            "GET https://example.org\nHTTP 200\n[Asserts]\nbody == ```json\n{\"query\": \"SELECT 1\"}\nSELECT * FROM items # not a comment\n```\nstatus == 200\n",
+        );
+        let asserts = asserts(&tree.entries[0]);
+        assert_eq!(
+            predicate_value(&asserts[0]),
+            &Value::MultilineString(
+                "{\"query\": \"SELECT 1\"}\nSELECT * FROM items # not a comment".to_string()
+            )
+        );
+        let value = asserts[0]
+            .predicate
+            .as_ref()
+            .unwrap()
+            .value
+            .as_ref()
+            .unwrap();
+        assert_eq!(value.span, Span::new(Pos::new(3, 8), Pos::new(6, 3)));
+        assert_eq!(asserts[1].query.name.value, "status");
+    }
+
+    #[test]
+    fn unterminated_multiline_predicate_value() {
+        let text = "GET https://example.org\nHTTP 200\n[Asserts]\nbody == ```\nhello\n\nGET https://example.org/next\n";
+        assert_eq!(
+            errors(text),
+            [(
+                Span::on_line(3, 8, 11),
+                "Unterminated multiline string, expected ```".to_string()
+            )]
+        );
+        let (tree, _) = parse(text);
+        assert_eq!(tree.entries.len(), 2);
+        assert_eq!(
+            predicate_value(&asserts(&tree.entries[0])[0]),
+            &Value::MultilineString("hello\n".to_string())
+        );
+    }
+
+    #[test]
+    fn bodies() {
+        let tree = parse_valid(
+            "POST https://example.org\n{\n  \"query\": \"[Asserts]\"\n}\nHTTP 200\n<?xml version=\"1.0\"?>\n<a><b/><!-- <c> --></a>\n\
+             POST https://example.org\n```graphql\nSELECT * FROM items\nGET https://example.org\n```\n\
+             POST https://example.org\n`hello`\n\
+             POST https://example.org\n\"hello\"\n\
+             POST https://example.org\nbase64,aGVsbG8=;\n\
+             POST https://example.org\nfile,data.bin;\n",
+        );
+        let bodies: Vec<(BodyKind, Span)> =
+            tree.bodies().map(|body| (body.kind, body.span)).collect();
+        assert_eq!(
+            bodies,
+            [
+                (BodyKind::Json, Span::new(Pos::new(1, 0), Pos::new(3, 1))),
+                (BodyKind::Xml, Span::new(Pos::new(5, 0), Pos::new(6, 23))),
+                (
+                    BodyKind::MultilineString,
+                    Span::new(Pos::new(8, 0), Pos::new(11, 3))
+                ),
+                (BodyKind::OnelineString, Span::on_line(13, 0, 7)),
+                (BodyKind::QuotedString, Span::on_line(15, 0, 7)),
+                (BodyKind::Base64, Span::on_line(17, 0, 16)),
+                (BodyKind::File, Span::on_line(19, 0, 14)),
+            ]
+        );
+        assert_eq!(tree.entries.len(), 6);
+    }
+
+    #[test]
+    fn unterminated_bodies_stop_at_the_next_entry() {
+        let text = "POST https://example.org\n{\n  \"a\": 1\n\nPOST https://example.org\n`hello\n";
+        assert_eq!(
+            errors(text),
+            [
+                (Span::on_line(1, 0, 1), "Unterminated JSON body".to_string()),
+                (
+                    Span::on_line(5, 0, 6),
+                    "Unterminated body, expected closing backtick".to_string()
+                ),
+            ]
+        );
+        let (tree, _) = parse(text);
+        assert_eq!(tree.entries.len(), 2);
+        let body = tree.entries[0].request.body.as_ref().unwrap();
+        assert_eq!(body.span, Span::new(Pos::new(1, 0), Pos::new(2, 8)));
+    }
+
+    #[test]
+    fn content_after_body() {
+        assert_eq!(
+            errors("POST https://example.org\n{\"a\": 1} x\n"),
+            [(
+                Span::on_line(1, 9, 10),
+                "Unexpected content after body".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn carriage_return_line_breaks() {
+        let tree = parse_valid("GET https://example.org\r\nHTTP 200\r[Asserts]\r\nstatus == 200\r");
+        let entry = &tree.entries[0];
+        assert_eq!(entry.response.as_ref().unwrap().status.value, "200");
+        assert_eq!(asserts(entry)[0].span, Span::on_line(3, 0, 13));
+    }
+
+    #[test]
+    fn comments_end_content() {
+        let chars: Vec<char> = "key: \"# kept\" `#` # comment".chars().collect();
+        assert_eq!(content_end(&chars), 17);
+        let chars: Vec<char> = "  # comment".chars().collect();
+        assert!(is_blank(&chars));
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+
+use crate::document::{Document, PositionEncoding};
+use crate::grammar;
+use crate::symbols::ExternalVariables;
+use crate::workspace::WorkspaceIndex;
+
+/// Places where the variable under the cursor is used, and defined with `include_declaration`.
+/// Besides the current document, they cover the other files of the workspace using the variable
+/// without defining it, whether open or not, and the variables file, unless the current
+/// document defines its own variable hiding the external one.
+pub fn get_references(
+    documents: &HashMap<String, Document>,
+    uri: &lsp_types::Url,
+    position: lsp_types::Position,
+    include_declaration: bool,
+    externals: &ExternalVariables,
+    index: &WorkspaceIndex,
+) -> Vec<lsp_types::Location> {
+    let Some((name, encoding)) = name_under_cursor(documents, uri, position) else {
+        return vec![];
+    };
+    let occurrences = occurrences(uri, &name, include_declaration, externals, index, encoding);
+    occurrences
+        .into_iter()
+        .flat_map(|(uri, ranges)| {
+            ranges
+                .into_iter()
+                .map(move |range| lsp_types::Location::new(uri.clone(), range))
+        })
+        .collect()
+}
+
+/// Range of the variable name under the cursor, if there is one to rename.
+pub fn prepare_rename(
+    document: &Document,
+    position: lsp_types::Position,
+) -> Option<lsp_types::PrepareRenameResponse> {
+    let positions = document.positions();
+    let (name, span) = document.symbols.name_at(positions.pos(position))?;
+    Some(lsp_types::PrepareRenameResponse::RangeWithPlaceholder {
+        range: positions.range(span),
+        placeholder: name.to_string(),
+    })
+}
+
+/// Edits renaming every occurrence of the variable under the cursor, or the reason the rename
+/// can't be done.
+pub fn rename(
+    documents: &HashMap<String, Document>,
+    uri: &lsp_types::Url,
+    position: lsp_types::Position,
+    new_name: &str,
+    externals: &ExternalVariables,
+    index: &WorkspaceIndex,
+) -> Result<Option<lsp_types::WorkspaceEdit>, String> {
+    if !grammar::is_variable_name(new_name) {
+        return Err(grammar::invalid_variable_name(new_name));
+    }
+    let Some((name, encoding)) = name_under_cursor(documents, uri, position) else {
+        return Ok(None);
+    };
+    let changes = occurrences(uri, &name, true, externals, index, encoding)
+        .into_iter()
+        .map(|(uri, ranges)| {
+            let edits = ranges
+                .into_iter()
+                .map(|range| lsp_types::TextEdit::new(range, new_name.to_string()))
+                .collect();
+            (uri, edits)
+        })
+        .collect();
+    Ok(Some(lsp_types::WorkspaceEdit::new(changes)))
+}
+
+/// Name of the variable under the cursor in the document at `uri`, along with the encoding of
+/// the positions exchanged about it.
+fn name_under_cursor(
+    documents: &HashMap<String, Document>,
+    uri: &lsp_types::Url,
+    position: lsp_types::Position,
+) -> Option<(String, PositionEncoding)> {
+    let document = documents.get(&uri.to_string())?;
+    let (name, _) = document
+        .symbols
+        .name_at(document.positions().pos(position))?;
+    Some((name.to_string(), document.encoding))
+}
+
+/// Ranges of `name` in each file it occurs in, as indexed from the open documents and from the
+/// files of the workspace on disk.
+fn occurrences(
+    uri: &lsp_types::Url,
+    name: &str,
+    include_declaration: bool,
+    externals: &ExternalVariables,
+    index: &WorkspaceIndex,
+    encoding: PositionEncoding,
+) -> Vec<(lsp_types::Url, Vec<lsp_types::Range>)> {
+    let shared = !externals.contains(name)
+        || !index
+            .variables()
+            .any(|(file_uri, variables)| file_uri == uri && variables.defines(name));
+    let mut occurrences = vec![];
+    for (file_uri, variables) in index.variables() {
+        if file_uri != uri && (!shared || variables.defines(name)) {
+            continue;
+        }
+        let definitions = variables.definitions.iter().filter(|_| include_declaration);
+        let ranges: Vec<lsp_types::Range> = definitions
+            .chain(&variables.references)
+            .filter(|(variable, _)| variable == name)
+            .map(|(_, range)| *range)
+            .collect();
+        if !ranges.is_empty() {
+            occurrences.push((file_uri.clone(), ranges));
+        }
+    }
+    if let (true, true, Some(file)) = (include_declaration, shared, &externals.file) {
+        let file_uri = lsp_types::Url::from_file_path(&file.path);
+        if let (Some(variable), Ok(file_uri)) = (file.find(name), file_uri) {
+            let range = file.positions(encoding).range(variable.span);
+            occurrences.push((file_uri, vec![range]));
+        }
+    }
+    occurrences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbols::VariablesFile;
+
+    /// Open documents and their index, from the URI and text of each file.
+    fn workspace(files: &[(&str, &str)]) -> (HashMap<String, Document>, WorkspaceIndex) {
+        let mut documents = HashMap::new();
+        let mut index = WorkspaceIndex::default();
+        for (uri, text) in files {
+            let document = Document::new(text, 0, PositionEncoding::Utf16);
+            index.update(lsp_types::Url::parse(uri).unwrap(), &document);
+            documents.insert(uri.to_string(), document);
+        }
+        (documents, index)
+    }
+
+    /// Lines of the references found from `position` of the document at `uri`, by file.
+    fn reference_lines(
+        files: &[(&str, &str)],
+        uri: &str,
+        position: lsp_types::Position,
+        include_declaration: bool,
+        externals: &ExternalVariables,
+    ) -> Vec<(String, u32)> {
+        let (documents, index) = workspace(files);
+        let uri = lsp_types::Url::parse(uri).unwrap();
+        let mut lines: Vec<(String, u32)> = get_references(
+            &documents,
+            &uri,
+            position,
+            include_declaration,
+            externals,
+            &index,
+        )
+        .into_iter()
+        .map(|location| (location.uri.to_string(), location.range.start.line))
+        .collect();
+        lines.sort();
+        lines
+    }
+
+    const CAPTURE: &str = "GET https://a.org\nHTTP 200\n[Captures]\ntoken: header \"Token\"\n\nGET https://a.org/{{token}}\n";
+    const USE: &str = "GET https://b.org/{{token}}\n";
+
+    #[test]
+    fn references_across_files() {
+        let files = [("file:///a.hurl", CAPTURE), ("file:///b.hurl", USE)];
+        let externals = ExternalVariables::default();
+        let position = lsp_types::Position::new(5, 21);
+        assert_eq!(
+            reference_lines(&files, "file:///a.hurl", position, true, &externals),
+            [
+                ("file:///a.hurl".to_string(), 3),
+                ("file:///a.hurl".to_string(), 5),
+                ("file:///b.hurl".to_string(), 0),
+            ]
+        );
+        assert_eq!(
+            reference_lines(&files, "file:///a.hurl", position, false, &externals),
+            [
+                ("file:///a.hurl".to_string(), 5),
+                ("file:///b.hurl".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn local_variables_hide_external_ones() {
+        let files = [
+            ("file:///a.hurl", CAPTURE),
+            ("file:///b.hurl", USE),
+            ("file:///c.hurl", USE),
+        ];
+        let externals = ExternalVariables {
+            file: None,
+            options: vec![("token".to_string(), "secret".to_string())],
+        };
+        assert_eq!(
+            reference_lines(
+                &files,
+                "file:///a.hurl",
+                lsp_types::Position::new(3, 1),
+                true,
+                &externals
+            ),
+            [
+                ("file:///a.hurl".to_string(), 3),
+                ("file:///a.hurl".to_string(), 5),
+            ]
+        );
+        // The external variable is shared by the files not defining their own.
+        assert_eq!(
+            reference_lines(
+                &files,
+                "file:///b.hurl",
+                lsp_types::Position::new(0, 21),
+                true,
+                &externals
+            ),
+            [
+                ("file:///b.hurl".to_string(), 0),
+                ("file:///c.hurl".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn references_include_the_variables_file() {
+        let path = std::env::temp_dir().join("hurlsp-references.properties");
+        std::fs::write(&path, "# Secrets\ntoken=secret\n").unwrap();
+        let externals = ExternalVariables {
+            file: Some(VariablesFile::load(&path).unwrap()),
+            options: vec![],
+        };
+        let file_uri = lsp_types::Url::from_file_path(&path).unwrap().to_string();
+        let files = [("file:///b.hurl", USE)];
+        let position = lsp_types::Position::new(0, 21);
+        assert_eq!(
+            reference_lines(&files, "file:///b.hurl", position, true, &externals),
+            [("file:///b.hurl".to_string(), 0), (file_uri, 1)]
+        );
+        assert_eq!(
+            reference_lines(&files, "file:///b.hurl", position, false, &externals),
+            [("file:///b.hurl".to_string(), 0)]
+        );
+        // A captured variable hides the one declared in the file.
+        let files = [("file:///a.hurl", CAPTURE)];
+        assert_eq!(
+            reference_lines(
+                &files,
+                "file:///a.hurl",
+                lsp_types::Position::new(5, 21),
+                true,
+                &externals
+            ),
+            [
+                ("file:///a.hurl".to_string(), 3),
+                ("file:///a.hurl".to_string(), 5),
+            ]
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Line, character and new text of a text edit.
+    type Edit = (u32, u32, String);
+
+    #[test]
+    fn rename_edits_each_file() {
+        let (documents, index) = workspace(&[("file:///a.hurl", CAPTURE), ("file:///b.hurl", USE)]);
+        let uri = lsp_types::Url::parse("file:///a.hurl").unwrap();
+        let edit = rename(
+            &documents,
+            &uri,
+            lsp_types::Position::new(5, 21),
+            "session",
+            &ExternalVariables::default(),
+            &index,
+        )
+        .unwrap()
+        .unwrap();
+        let mut changes: Vec<(String, Vec<Edit>)> = edit
+            .changes
+            .unwrap()
+            .into_iter()
+            .map(|(uri, edits)| {
+                let mut edits: Vec<Edit> = edits
+                    .into_iter()
+                    .map(|edit| {
+                        (
+                            edit.range.start.line,
+                            edit.range.start.character,
+                            edit.new_text,
+                        )
+                    })
+                    .collect();
+                edits.sort();
+                (uri.to_string(), edits)
+            })
+            .collect();
+        changes.sort();
+        assert_eq!(
+            changes,
+            [
+                (
+                    "file:///a.hurl".to_string(),
+                    vec![
+                        (3, 0, "session".to_string()),
+                        (5, 20, "session".to_string())
+                    ]
+                ),
+                (
+                    "file:///b.hurl".to_string(),
+                    vec![(0, 20, "session".to_string())]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn rename_checks_the_new_name() {
+        let (documents, index) = workspace(&[("file:///b.hurl", USE)]);
+        let uri = lsp_types::Url::parse("file:///b.hurl").unwrap();
+        let rename = |character: u32, new_name: &str| {
+            rename(
+                &documents,
+                &uri,
+                lsp_types::Position::new(0, character),
+                new_name,
+                &ExternalVariables::default(),
+                &index,
+            )
+        };
+        assert_eq!(
+            rename(21, "1st"),
+            Err(grammar::invalid_variable_name("1st"))
+        );
+        assert!(rename(21, "first-token").unwrap().is_some());
+        // Nothing to rename outside a variable.
+        assert_eq!(rename(1, "name"), Ok(None));
+    }
+}
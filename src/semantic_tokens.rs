@@ -0,0 +1,370 @@
+// Semantic tokens for editors without a Hurl grammar, taken from the parse tree and the
+// templates of the symbol table, and delta-encoded as the protocol expects.
+
+use crate::ast::{
+    Assert, Capture, Filter, KeyValue, Predicate, Query, SectionContent, Span, Spanned, Value,
+};
+use crate::document::{Document, Positions};
+use crate::parser;
+
+/// Kinds of token in the order of the legend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenType {
+    Method,
+    Url,
+    Section,
+    HeaderName,
+    Query,
+    Filter,
+    Predicate,
+    Variable,
+    String,
+    Number,
+    Regex,
+    Keyword,
+    Comment,
+}
+
+const TOKEN_TYPES: &[(TokenType, lsp_types::SemanticTokenType)] = &[
+    (TokenType::Method, lsp_types::SemanticTokenType::KEYWORD),
+    (TokenType::Url, lsp_types::SemanticTokenType::STRING),
+    (TokenType::Section, lsp_types::SemanticTokenType::NAMESPACE),
+    (
+        TokenType::HeaderName,
+        lsp_types::SemanticTokenType::PROPERTY,
+    ),
+    (TokenType::Query, lsp_types::SemanticTokenType::FUNCTION),
+    (TokenType::Filter, lsp_types::SemanticTokenType::METHOD),
+    (TokenType::Predicate, lsp_types::SemanticTokenType::OPERATOR),
+    (TokenType::Variable, lsp_types::SemanticTokenType::VARIABLE),
+    (TokenType::String, lsp_types::SemanticTokenType::STRING),
+    (TokenType::Number, lsp_types::SemanticTokenType::NUMBER),
+    (TokenType::Regex, lsp_types::SemanticTokenType::REGEXP),
+    (TokenType::Keyword, lsp_types::SemanticTokenType::KEYWORD),
+    (TokenType::Comment, lsp_types::SemanticTokenType::COMMENT),
+];
+
+/// Modifier set on the name of a capture, where its variable is defined.
+const DECLARATION: u32 = 1;
+
+pub fn legend() -> lsp_types::SemanticTokensLegend {
+    lsp_types::SemanticTokensLegend {
+        token_types: TOKEN_TYPES
+            .iter()
+            .map(|(_, token_type)| token_type.clone())
+            .collect(),
+        token_modifiers: vec![lsp_types::SemanticTokenModifier::DECLARATION],
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Token {
+    span: Span,
+    token_type: TokenType,
+    modifiers: u32,
+}
+
+/// Tokens of the whole document or, with `range`, of the lines it covers.
+pub fn get_semantic_tokens(
+    document: &Document,
+    range: Option<lsp_types::Range>,
+) -> lsp_types::SemanticTokens {
+    let mut tokens = vec![];
+    for entry in &document.tree.entries {
+        let request = &entry.request;
+        push(&mut tokens, request.method.span, TokenType::Method);
+        push(&mut tokens, request.url.span, TokenType::Url);
+        key_values(&mut tokens, &request.headers);
+        if let Some(response) = &entry.response {
+            push(&mut tokens, response.version.span, TokenType::Keyword);
+            push(&mut tokens, response.status.span, TokenType::Number);
+            key_values(&mut tokens, &response.headers);
+        }
+        for section in entry.sections() {
+            push(&mut tokens, section.name.span, TokenType::Section);
+            match &section.content {
+                SectionContent::KeyValues(pairs) => key_values(&mut tokens, pairs),
+                SectionContent::Captures(captures) => captures
+                    .iter()
+                    .for_each(|capture| capture_tokens(&mut tokens, capture)),
+                SectionContent::Asserts(asserts) => asserts
+                    .iter()
+                    .for_each(|assert| assert_tokens(&mut tokens, assert)),
+            }
+        }
+    }
+    tokens.extend(comment_tokens(document));
+
+    // Templates are highlighted over whatever contains them.
+    let templates: Vec<Span> = document
+        .symbols
+        .references
+        .iter()
+        .map(|reference| reference.span)
+        .collect();
+    let mut tokens: Vec<Token> = tokens
+        .into_iter()
+        .flat_map(|token| split_lines(document, token))
+        .flat_map(|token| cut_out(token, &templates))
+        .chain(templates.iter().map(|span| Token {
+            span: *span,
+            token_type: TokenType::Variable,
+            modifiers: 0,
+        }))
+        .filter(|token| token.span.end.column > token.span.start.column)
+        .collect();
+    if let Some(range) = range {
+        let start = usize::try_from(range.start.line).unwrap();
+        let end = usize::try_from(range.end.line).unwrap();
+        tokens.retain(|token| start <= token.span.start.line && token.span.start.line <= end);
+    }
+    tokens.sort_by_key(|token| token.span.start);
+    lsp_types::SemanticTokens {
+        result_id: None,
+        data: encode(&tokens, document.positions()),
+    }
+}
+
+fn push(tokens: &mut Vec<Token>, span: Span, token_type: TokenType) {
+    tokens.push(Token {
+        span,
+        token_type,
+        modifiers: 0,
+    });
+}
+
+fn key_values(tokens: &mut Vec<Token>, key_values: &[KeyValue]) {
+    for key_value in key_values {
+        push(tokens, key_value.key.span, TokenType::HeaderName);
+        push(tokens, key_value.value.span, TokenType::String);
+    }
+}
+
+fn capture_tokens(tokens: &mut Vec<Token>, capture: &Capture) {
+    tokens.push(Token {
+        span: capture.name.span,
+        token_type: TokenType::Variable,
+        modifiers: DECLARATION,
+    });
+    if let Some(query) = &capture.query {
+        query_tokens(tokens, query);
+    }
+    filter_tokens(tokens, &capture.filters);
+}
+
+fn assert_tokens(tokens: &mut Vec<Token>, assert: &Assert) {
+    query_tokens(tokens, &assert.query);
+    filter_tokens(tokens, &assert.filters);
+    if let Some(predicate) = &assert.predicate {
+        predicate_tokens(tokens, predicate);
+    }
+}
+
+fn query_tokens(tokens: &mut Vec<Token>, query: &Query) {
+    push(tokens, query.name.span, TokenType::Query);
+    if let Some(argument) = &query.argument {
+        value_token(tokens, argument);
+    }
+}
+
+fn filter_tokens(tokens: &mut Vec<Token>, filters: &[Filter]) {
+    for filter in filters {
+        push(tokens, filter.name.span, TokenType::Filter);
+        for argument in &filter.arguments {
+            value_token(tokens, argument);
+        }
+    }
+}
+
+fn predicate_tokens(tokens: &mut Vec<Token>, predicate: &Predicate) {
+    if let Some(not) = predicate.not {
+        push(tokens, not, TokenType::Keyword);
+    }
+    push(tokens, predicate.name.span, TokenType::Predicate);
+    if let Some(value) = &predicate.value {
+        value_token(tokens, value);
+    }
+}
+
+fn value_token(tokens: &mut Vec<Token>, value: &Spanned<Value>) {
+    let token_type = match value.value {
+        Value::String(_) | Value::MultilineString(_) | Value::Bytes(_) => TokenType::String,
+        Value::Regex(_) => TokenType::Regex,
+        Value::Integer(_) | Value::Float(_) => TokenType::Number,
+        Value::Bool(_) | Value::Null => TokenType::Keyword,
+        Value::Template(_) => TokenType::Variable,
+        Value::Word(_) => return,
+    };
+    push(tokens, value.span, token_type);
+}
+
+/// Trailing comments and comment lines, leaving out bodies and ``` strings where `#` is just
+/// another char.
+fn comment_tokens(document: &Document) -> Vec<Token> {
+    let verbatim = document.tree.verbatim_lines(document.content.len_lines());
+    let mut tokens = vec![];
+    for (line, verbatim) in verbatim.into_iter().enumerate() {
+        if verbatim {
+            continue;
+        }
+        let chars = document.line(line);
+        let start = parser::content_end(chars);
+        if let Some(column) = (start..chars.len()).find(|column| chars[*column] == '#') {
+            push(
+                &mut tokens,
+                Span::on_line(line, column, chars.len()),
+                TokenType::Comment,
+            );
+        }
+    }
+    tokens
+}
+
+/// Pieces of a token on each of the lines it spans, since clients may not support tokens
+/// running over several lines.
+fn split_lines(document: &Document, token: Token) -> Vec<Token> {
+    let Span { start, end } = token.span;
+    (start.line..=end.line)
+        .map(|line| {
+            let first = if line == start.line { start.column } else { 0 };
+            let last = if line == end.line {
+                end.column
+            } else {
+                document.line(line).len()
+            };
+            Token {
+                span: Span::on_line(line, first, last),
+                ..token
+            }
+        })
+        .collect()
+}
+
+/// Pieces of a single line token left around the `holes` it overlaps.
+fn cut_out(token: Token, holes: &[Span]) -> Vec<Token> {
+    let line = token.span.start.line;
+    let mut pieces = vec![];
+    let mut start = token.span.start.column;
+    let end = token.span.end.column;
+    let mut holes: Vec<&Span> = holes
+        .iter()
+        .filter(|hole| {
+            hole.start.line == line && hole.start.column < end && hole.end.column > start
+        })
+        .collect();
+    holes.sort_by_key(|hole| hole.start.column);
+    for hole in holes {
+        if hole.start.column > start {
+            pieces.push(Span::on_line(line, start, hole.start.column));
+        }
+        start = start.max(hole.end.column);
+    }
+    if start < end {
+        pieces.push(Span::on_line(line, start, end));
+    }
+    pieces
+        .into_iter()
+        .map(|span| Token { span, ..token })
+        .collect()
+}
+
+/// Tokens as five integers each, with the line and start relative to the previous token.
+fn encode(tokens: &[Token], positions: Positions) -> Vec<lsp_types::SemanticToken> {
+    let mut previous = lsp_types::Position::default();
+    tokens
+        .iter()
+        .map(|token| {
+            let start = positions.position(token.span.start);
+            let end = positions.position(token.span.end);
+            let delta_line = start.line - previous.line;
+            let delta_start = if delta_line == 0 {
+                start.character - previous.character
+            } else {
+                start.character
+            };
+            previous = start;
+            let token_type = TOKEN_TYPES
+                .iter()
+                .position(|(token_type, _)| *token_type == token.token_type)
+                .unwrap();
+            lsp_types::SemanticToken {
+                delta_line,
+                delta_start,
+                length: end.character - start.character,
+                token_type: token_type.try_into().unwrap(),
+                token_modifiers_bitset: token.modifiers,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::PositionEncoding;
+
+    /// Tokens of `text` as absolute line, start, length and type.
+    fn decode(text: &str) -> Vec<(u32, u32, u32, TokenType)> {
+        let document = Document::new(text, 0, PositionEncoding::Utf16);
+        let (mut line, mut start) = (0, 0);
+        get_semantic_tokens(&document, None)
+            .data
+            .into_iter()
+            .map(|token| {
+                if token.delta_line > 0 {
+                    start = 0;
+                }
+                line += token.delta_line;
+                start += token.delta_start;
+                let token_type = TOKEN_TYPES[usize::try_from(token.token_type).unwrap()].0;
+                (line, start, token.length, token_type)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tokens_of_a_document() {
+        let tokens = decode(
+            "GET https://a.org/{{id}}\n\
+             X-Token: a{{token}}b # note\n\
+             HTTP 200\n\
+             [Asserts]\n\
+             body == ```\n\
+             one\n\
+             two\n\
+             ```\n",
+        );
+        assert_eq!(
+            tokens,
+            [
+                (0, 0, 3, TokenType::Method),
+                (0, 4, 16, TokenType::Url),
+                (0, 20, 2, TokenType::Variable),
+                (0, 22, 2, TokenType::Url),
+                (1, 0, 7, TokenType::HeaderName),
+                (1, 9, 3, TokenType::String),
+                (1, 12, 5, TokenType::Variable),
+                (1, 17, 3, TokenType::String),
+                (1, 21, 6, TokenType::Comment),
+                (2, 0, 4, TokenType::Keyword),
+                (2, 5, 3, TokenType::Number),
+                (3, 0, 9, TokenType::Section),
+                (4, 0, 4, TokenType::Query),
+                (4, 5, 2, TokenType::Predicate),
+                (4, 8, 3, TokenType::String),
+                (5, 0, 3, TokenType::String),
+                (6, 0, 3, TokenType::String),
+                (7, 0, 3, TokenType::String),
+            ]
+        );
+        for pair in tokens.windows(2) {
+            let ((line, start, length, _), (next_line, next_start, _, _)) = (pair[0], pair[1]);
+            assert!(
+                line < next_line || start + length <= next_start,
+                "{:?} overlaps {:?}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+}
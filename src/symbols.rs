@@ -0,0 +1,247 @@
+// Variables a document defines and the templates using them, so templates can be checked and
+// completed against the variables set by the time they are rendered.
+
+use std::path::{Path, PathBuf};
+
+use ropey::Rope;
+
+use crate::ast::{Entry, HurlFile, Pos, SectionContent, SectionKind, Span, Spanned};
+use crate::document::{PositionEncoding, Positions};
+use crate::grammar;
+use crate::parser;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    /// A `name: query` line of a `[Captures]` section.
+    Capture,
+    /// A `variable: name=value` line of an `[Options]` section.
+    Option,
+}
+
+/// A variable defined by an entry of the document.
+#[derive(Debug, Clone)]
+pub struct Variable {
+    pub name: String,
+    /// Span of the name where it is defined.
+    pub span: Span,
+    pub origin: Origin,
+    /// First line whose templates can use the variable. Captures are only set once the response
+    /// is received while options apply to the whole entry.
+    pub scope_start: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    /// Variables in the order they are defined in the document.
+    pub variables: Vec<Variable>,
+    /// Variable names used in `{{ }}` templates, spanning the name inside the braces.
+    pub references: Vec<Spanned<String>>,
+}
+
+impl SymbolTable {
+    pub fn new(tree: &HurlFile, lines: &[Vec<char>]) -> Self {
+        let mut variables = vec![];
+        for entry in &tree.entries {
+            for section in entry.sections() {
+                match &section.content {
+                    SectionContent::Captures(captures) => {
+                        let scope_start = capture_scope_start(entry);
+                        variables.extend(captures.iter().map(|capture| Variable {
+                            name: capture.name.value.clone(),
+                            span: capture.name.span,
+                            origin: Origin::Capture,
+                            scope_start,
+                        }));
+                    }
+                    SectionContent::KeyValues(options) if section.kind == SectionKind::Options => {
+                        variables.extend(
+                            options
+                                .iter()
+                                .filter(|option| option.key.value == "variable")
+                                .filter_map(|option| {
+                                    let value = &option.value;
+                                    let (name, _) = value.value.split_once('=')?;
+                                    let start = value.span.start.column
+                                        + name.chars().take_while(|c| c.is_whitespace()).count();
+                                    let name = name.trim();
+                                    grammar::is_variable_name(name).then(|| Variable {
+                                        name: name.to_string(),
+                                        span: Span::on_line(
+                                            value.span.start.line,
+                                            start,
+                                            start + name.chars().count(),
+                                        ),
+                                        origin: Origin::Option,
+                                        scope_start: entry.span.start.line,
+                                    })
+                                }),
+                        );
+                    }
+                    SectionContent::KeyValues(_) | SectionContent::Asserts(_) => {}
+                }
+            }
+        }
+        SymbolTable {
+            variables,
+            references: template_references(tree, lines),
+        }
+    }
+
+    /// Variables set by the time the templates of `line` are rendered, the last definition of
+    /// each name first.
+    pub fn visible_at(&self, line: usize) -> impl Iterator<Item = &Variable> {
+        self.variables
+            .iter()
+            .rev()
+            .filter(move |variable| variable.scope_start <= line)
+    }
+
+    /// Name and span of the variable under `pos`, either where it is defined or used.
+    pub fn name_at(&self, pos: Pos) -> Option<(&str, Span)> {
+        let definitions = self
+            .variables
+            .iter()
+            .map(|variable| (variable.name.as_str(), variable.span));
+        let references = self
+            .references
+            .iter()
+            .map(|reference| (reference.value.as_str(), reference.span));
+        definitions
+            .chain(references)
+            .find(|(_, span)| span.contains(pos))
+    }
+
+    pub fn find(&self, name: &str) -> impl Iterator<Item = &Variable> {
+        let name = name.to_string();
+        self.variables
+            .iter()
+            .filter(move |variable| variable.name == name)
+    }
+}
+
+/// Templates of every line, looking into trailing comments only in bodies and ``` strings where
+/// `#` is just another char.
+fn template_references(tree: &HurlFile, lines: &[Vec<char>]) -> Vec<Spanned<String>> {
+    let verbatim = tree.verbatim_lines(lines.len());
+    let mut references = vec![];
+    for (index, chars) in lines.iter().enumerate() {
+        let end = if verbatim[index] {
+            chars.len()
+        } else {
+            parser::content_end(chars)
+        };
+        let mut column = 0;
+        while column + 1 < end {
+            if chars[column] != '{' || chars[column + 1] != '{' {
+                column += 1;
+                continue;
+            }
+            let start = column + 2;
+            let Some(close) =
+                (start..end.saturating_sub(1)).find(|i| chars[*i] == '}' && chars[*i + 1] == '}')
+            else {
+                break;
+            };
+            let content = &chars[start..close];
+            let leading = content.iter().take_while(|c| c.is_whitespace()).count();
+            let name: String = content.iter().collect::<String>().trim().to_string();
+            if grammar::is_variable_name(&name) && !grammar::FUNCTIONS.contains(&name.as_str()) {
+                let name_start = start + leading;
+                references.push(Spanned {
+                    span: Span::new(
+                        Pos::new(index, name_start),
+                        Pos::new(index, name_start + name.chars().count()),
+                    ),
+                    value: name,
+                });
+            }
+            column = close + 2;
+        }
+    }
+    references
+}
+
+/// Captures are evaluated once the response is received, so the response part of their entry
+/// can already use them.
+fn capture_scope_start(entry: &Entry) -> usize {
+    entry
+        .response
+        .as_ref()
+        .map_or(entry.span.end.line, |response| response.span.start.line)
+}
+
+/// A variable of the variables file given to Hurl with `--variables-file`.
+#[derive(Debug, Clone)]
+pub struct FileVariable {
+    pub name: String,
+    pub value: String,
+    /// Span of the name in the file.
+    pub span: Span,
+}
+
+/// A `name=value` properties file providing variables to every Hurl file of the workspace.
+#[derive(Debug, Clone)]
+pub struct VariablesFile {
+    pub path: PathBuf,
+    pub variables: Vec<FileVariable>,
+    content: Rope,
+}
+
+impl VariablesFile {
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(VariablesFile {
+            path: path.to_path_buf(),
+            variables: parse_variables(&text),
+            content: Rope::from_str(&text),
+        })
+    }
+
+    pub fn positions(&self, encoding: PositionEncoding) -> Positions<'_> {
+        Positions::new(&self.content, encoding)
+    }
+
+    pub fn find(&self, name: &str) -> Option<&FileVariable> {
+        self.variables.iter().find(|variable| variable.name == name)
+    }
+}
+
+/// Variables provided to the server rather than defined by the documents: those of the
+/// variables file and those given directly in the initialization options.
+#[derive(Debug, Default)]
+pub struct ExternalVariables {
+    pub file: Option<VariablesFile>,
+    /// Name and value of the `variables` initialization option entries.
+    pub options: Vec<(String, String)>,
+}
+
+impl ExternalVariables {
+    pub fn contains(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == name)
+            || self
+                .file
+                .as_ref()
+                .is_some_and(|file| file.find(name).is_some())
+    }
+}
+
+/// Variables of a properties file, skipping blank lines and `#` comments.
+fn parse_variables(text: &str) -> Vec<FileVariable> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(line, content)| {
+            let start = content.chars().take_while(|c| c.is_whitespace()).count();
+            let content = content.trim();
+            if content.is_empty() || content.starts_with('#') {
+                return None;
+            }
+            let (name, value) = content.split_once('=')?;
+            let name = name.trim_end();
+            Some(FileVariable {
+                name: name.to_string(),
+                value: value.trim().to_string(),
+                span: Span::on_line(line, start, start + name.chars().count()),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,171 @@
+// Symbols and variables of every Hurl file under the workspace roots, read from disk at startup
+// and kept up to date with the open documents and the changes made on disk, for
+// `workspace/symbol` and for the references and renames reaching files that aren't open.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use log::error;
+
+use crate::ast::HurlFile;
+use crate::document::{Document, PositionEncoding, Positions};
+use crate::outline;
+use crate::symbols::SymbolTable;
+
+#[derive(Debug, Default)]
+pub struct WorkspaceIndex {
+    files: HashMap<lsp_types::Url, IndexedFile>,
+    /// Encoding of the positions of the files read from disk.
+    encoding: PositionEncoding,
+}
+
+#[derive(Debug)]
+struct IndexedFile {
+    symbols: Vec<lsp_types::SymbolInformation>,
+    variables: FileVariables,
+}
+
+/// Variables a file defines and uses in templates, with the ranges of their names.
+#[derive(Debug, Default)]
+pub struct FileVariables {
+    pub definitions: Vec<(String, lsp_types::Range)>,
+    pub references: Vec<(String, lsp_types::Range)>,
+}
+
+impl FileVariables {
+    pub fn new(symbols: &SymbolTable, positions: Positions) -> Self {
+        FileVariables {
+            definitions: symbols
+                .variables
+                .iter()
+                .map(|variable| (variable.name.clone(), positions.range(variable.span)))
+                .collect(),
+            references: symbols
+                .references
+                .iter()
+                .map(|reference| (reference.value.clone(), positions.range(reference.span)))
+                .collect(),
+        }
+    }
+
+    pub fn defines(&self, name: &str) -> bool {
+        self.definitions
+            .iter()
+            .any(|(definition, _)| definition == name)
+    }
+}
+
+impl WorkspaceIndex {
+    /// Indexes the `.hurl` files found under `roots`, leaving out hidden directories.
+    pub fn new(roots: &[PathBuf], encoding: PositionEncoding) -> Self {
+        let mut index = WorkspaceIndex {
+            files: HashMap::new(),
+            encoding,
+        };
+        let mut paths = vec![];
+        for root in roots {
+            find_hurl_files(root, &mut paths);
+        }
+        for path in paths {
+            index.load(&path);
+        }
+        index
+    }
+
+    /// Replaces the symbols of a file with those of its content on disk.
+    pub fn load(&mut self, path: &Path) {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                error!("Failed to read {}: {}", path.display(), e);
+                return;
+            }
+        };
+        if let Ok(uri) = lsp_types::Url::from_file_path(path) {
+            self.update(uri, &Document::new(&text, 0, self.encoding));
+        }
+    }
+
+    /// Replaces the symbols and variables of a file with those of its current content.
+    pub fn update(&mut self, uri: lsp_types::Url, document: &Document) {
+        let positions = document.positions();
+        let file = IndexedFile {
+            symbols: file_symbols(&uri, &document.tree, positions),
+            variables: FileVariables::new(&document.symbols, positions),
+        };
+        self.files.insert(uri, file);
+    }
+
+    /// Variables of every file indexed, open or not.
+    pub fn variables(&self) -> impl Iterator<Item = (&lsp_types::Url, &FileVariables)> {
+        self.files.iter().map(|(uri, file)| (uri, &file.variables))
+    }
+
+    pub fn remove(&mut self, uri: &lsp_types::Url) {
+        self.files.remove(uri);
+    }
+
+    /// Symbols whose name contains every word of `query`, ignoring case. An empty query matches
+    /// everything.
+    pub fn search(&self, query: &str) -> Vec<lsp_types::SymbolInformation> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let mut symbols: Vec<lsp_types::SymbolInformation> = self
+            .files
+            .values()
+            .flat_map(|file| &file.symbols)
+            .filter(|symbol| {
+                let name = symbol.name.to_lowercase();
+                words.iter().all(|word| name.contains(word.as_str()))
+            })
+            .cloned()
+            .collect();
+        symbols.sort_by(|a, b| {
+            (a.location.uri.as_str(), a.location.range.start.line)
+                .cmp(&(b.location.uri.as_str(), b.location.range.start.line))
+        });
+        symbols
+    }
+}
+
+fn find_hurl_files(directory: &Path, paths: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(e) => {
+            error!("Failed to read directory {}: {}", directory.display(), e);
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        let hurl = path
+            .extension()
+            .is_some_and(|extension| extension == "hurl");
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() && !hidden => find_hurl_files(&path, paths),
+            Ok(file_type) if file_type.is_file() && hurl => paths.push(path),
+            _ => {}
+        }
+    }
+}
+
+/// An entry per request, named after its method and URL, and the variables it captures.
+fn file_symbols(
+    uri: &lsp_types::Url,
+    tree: &HurlFile,
+    positions: Positions,
+) -> Vec<lsp_types::SymbolInformation> {
+    let mut symbols = vec![];
+    for entry in outline::get_document_symbols(tree, positions) {
+        let captures: Vec<lsp_types::SymbolInformation> = entry
+            .children
+            .iter()
+            .flatten()
+            .flat_map(|section| section.children.iter().flatten())
+            .map(|capture| outline::symbol_information(capture, uri, Some(entry.name.clone())))
+            .collect();
+        symbols.push(outline::symbol_information(&entry, uri, None));
+        symbols.extend(captures);
+    }
+    symbols
+}